@@ -0,0 +1,3 @@
+anchor_gen::generate_cpi_crate!("idl.json");
+
+declare_id!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
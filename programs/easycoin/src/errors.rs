@@ -52,4 +52,71 @@ pub enum AgentError {
     // TestError,
     FeeIndexInvalid,
     FeesLengthInvalid,
+
+    OperatorLedgerOverflow,
+    JitoTipAlreadyInTransaction,
+
+    AuthoritySignerLimitReached,
+    AuthoritySignersInvalid,
+
+    AuthorityTransferNotPending,
+    PendingAuthorityNotAllowed,
+    PauserIsSame,
+    PauserTransferNotPending,
+    PendingPauserNotAllowed,
+
+    TimelockDelayInvalid,
+    ProposalIdOverflow,
+    ProposalNotReady,
+
+    PauseFeaturesInvalid,
+    FeaturePaused,
+    PauseDurationInvalid,
+
+    OperatorRolesInvalid,
+    OperatorVolumeQuotaExceeded,
+    OperatorAccountInvalid,
+
+    WindDownSellOnly,
+
+    UpgradeAuthorityNotAllowed,
+    InitializeArgsInvalid,
+
+    UserTokenAccountInvalid,
+    UserTokenAccountNotEmpty,
+
+    UnwrapAmountInvalid,
+    AutoWrapAccountsMissing,
+
+    JupiterRouteDecodeFailed,
+    JupiterSlippageExceeded,
+    JupiterPlatformFeeExceeded,
+    JupiterRouteAmountInvalid,
+    SlippageLimitInvalid,
+
+    PlatformFeeAccountInvalid,
+    PlatformFeeTooLow,
+
+    RaydiumPoolInvalid,
+    RaydiumTokenAccountInvalid,
+    RaydiumAmountInvalid,
+    RaydiumExceedSlippage,
+
+    PumpfunBondingCurveComplete,
+    NotPumpswapTrade,
+    PumpswapPoolInvalid,
+    PumpswapUserAccountInvalid,
+    PumpswapUserTokenAccountInvalid,
+    PumpswapAmountInvalid,
+    PumpswapExceedSlippage,
+
+    TxFeeAlreadyInTransaction,
+
+    RegistryMigrationInvalid,
+
+    PauseCooldown,
+
+    FeeCollectorMissing,
+
+    PlatformFeeLimitInvalid,
 }
@@ -1,21 +1,376 @@
 use anchor_lang::prelude::*;
 
+use crate::state::ProposalAction;
+
+/******* management related ********/
+
 #[event]
-pub struct UserTokenAccountClosedEvent {
+pub struct ProgramInitializedEvent {
+    pub operator_registry_authority: Pubkey,
+}
+
+#[event]
+pub struct ProposalQueuedEvent {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub eta: i64,
+    pub action: ProposalAction,
+}
+
+#[event]
+pub struct ProposalExecutedEvent {
+    pub id: u64,
+}
+
+#[event]
+pub struct ProposalCancelledEvent {
+    pub id: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TimelockDelaySetEvent {
+    pub old_delay: i64,
+    pub new_delay: i64,
+}
+
+#[event]
+pub struct AuthorityTransferProposedEvent {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferCancelledEvent {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferredEvent {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthoritySignersSetEvent {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct OperatorAddedEvent {
+    pub authority: Pubkey,
+    pub operator: Pubkey,
+    pub roles: u8,
+}
+
+#[event]
+pub struct OperatorRolesSetEvent {
+    pub authority: Pubkey,
+    pub operator: Pubkey,
+    pub roles: u8,
+}
+
+#[event]
+pub struct OperatorQuotaSetEvent {
+    pub authority: Pubkey,
+    pub operator: Pubkey,
+    pub expires_at: i64,
+    pub max_volume_per_epoch: u64,
+}
+
+#[event]
+pub struct OperatorRemovedEvent {
+    pub authority: Pubkey,
+    pub operator: Pubkey,
+}
+
+#[event]
+pub struct FeeSetEvent {
+    pub authority: Pubkey,
+    pub fee_index: u8,
+    pub value: u64,
+}
+
+#[event]
+pub struct FeeCollectorAddedEvent {
+    pub authority: Pubkey,
+    pub fee_collector: Pubkey,
+}
+
+#[event]
+pub struct FeeCollectorRemovedEvent {
+    pub authority: Pubkey,
+    pub fee_collector: Pubkey,
+}
+
+#[event]
+pub struct PauserTransferProposedEvent {
+    pub authority: Pubkey,
+    pub pending_pauser: Pubkey,
+}
+
+#[event]
+pub struct PauserTransferCancelledEvent {
+    pub authority: Pubkey,
+    pub pending_pauser: Pubkey,
+}
+
+#[event]
+pub struct PauserAddedEvent {
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct PauserRemovedEvent {
+    pub authority: Pubkey,
+    pub pauser: Pubkey,
+}
+
+#[event]
+pub struct MaxPauseDurationSetEvent {
+    pub old_duration: i64,
+    pub new_duration: i64,
+}
+
+#[event]
+pub struct ProgramPausedEvent {
+    pub pauser: Pubkey,
+    pub paused_until: i64,
+}
+
+#[event]
+pub struct PauseExtendedEvent {
+    pub authority: Pubkey,
+    pub paused_until: i64,
+}
+
+#[event]
+pub struct FeaturesPausedEvent {
+    pub pauser: Pubkey,
+    pub features: u16,
+    pub paused_features: u16,
+    pub paused_until: i64,
+}
+
+#[event]
+pub struct FeaturesPauseExtendedEvent {
+    pub authority: Pubkey,
+    pub paused_features: u16,
+    pub paused_until: i64,
+}
+
+#[event]
+pub struct FeaturesUnpausedEvent {
+    pub authority: Pubkey,
+    pub features: u16,
+    pub paused_features: u16,
+}
+
+#[event]
+pub struct ProgramUnpausedEvent {
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ProgramWindDownEvent {
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RegistryMigratedEvent {
+    pub authority: Pubkey,
+    pub registry: Pubkey,
+}
+
+/******* user management related ********/
+
+#[event]
+pub struct OwnerAccountCreatedEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+}
+
+#[event]
+pub struct OwnerAccountClosedEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+}
+
+#[event]
+pub struct OwnerSwapLimitsSetEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+    pub max_slippage_bps: u16,
+    pub max_platform_fee_bps: u16,
+}
+
+#[event]
+pub struct UserAccountCreatedEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+}
+
+#[event]
+pub struct UserAccountClosedEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+}
+
+#[event]
+pub struct WithdrawEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DueFeeSettledEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub fee_collector: Pubkey,
+    pub due_fee: u64,
+    pub settled_fee: u64, // less than due_fee if the balance is not enough
+}
+
+#[event]
+pub struct TokenWithdrawEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct OwnerTokenAccountClosedEvent {
+    pub owner: Pubkey,
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub lamports: u64,
+}
+
+/******* operator related ********/
+
+#[event]
+pub struct UserTokenAccountCreatedEvent {
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
     pub token_account: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct UserTokenAccountClosedEvent {
+    pub owner_account: Pubkey,
     pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
     pub lamports: u64,
 }
 
+#[event]
+pub struct WsolWrappedEvent {
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
+    pub wsol_token_account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WsolUnwrappedEvent {
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
+    pub wsol_token_account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SwapEvent {
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
+    pub venue_program: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub trade_fee: u64,
+}
+
+// trade fee taken by the venue into a fee token account of the fee registry
+#[event]
+pub struct PlatformFeeTakenEvent {
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
+    pub platform_fee_account: Pubkey,
+    pub mint: Pubkey,
+    pub platform_fee_bps: u8,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PlatformFeeCollectedEvent {
+    pub operator: Pubkey,
+    pub platform_fee_account: Pubkey,
+    pub fee_collector: Pubkey,
+    pub destination_token_account: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
 #[event]
 pub struct FeeCollectedEvent {
+    pub owner_account: Pubkey,
+    pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
+    pub tx_fee: u64,
+    pub trade_fee: u64,
+}
+
+#[event]
+pub struct OperatorFeeReimbursedEvent {
+    pub owner_account: Pubkey,
     pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
     pub tx_fee: u64,
+    pub reimbursed: u64,
     pub trade_fee: u64,
 }
 
 #[event]
 pub struct TipJitoEvent {
+    pub owner_account: Pubkey,
     pub user_account: Pubkey,
+    pub nonce: u32,
+    pub operator: Pubkey,
+    pub jito_tip_account: Pubkey,
     pub tip_amount: u64,
-}
\ No newline at end of file
+}
@@ -17,6 +17,23 @@ impl anchor_lang::Id for Pumpfun {
 }
 
 
+// pump.fun AMM, tokens graduate to it once the bonding curve is complete
+mod pumpswap {
+    use anchor_lang::declare_id;
+
+    declare_id!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+}
+
+#[derive(Clone)]
+pub struct Pumpswap;
+
+impl anchor_lang::Id for Pumpswap {
+    fn id() -> Pubkey {
+        pumpswap::id()
+    }
+}
+
+
 
 mod jupiter {
     use anchor_lang::declare_id;
@@ -33,6 +50,34 @@ impl anchor_lang::Id for Jupiter {
     }
 }
 
+impl Jupiter {
+    // program authority of shared accounts routes, seeded by "authority" and the authority id
+    pub fn program_authority(id: u8) -> Pubkey {
+        Pubkey::find_program_address(&[b"authority", &[id]], &jupiter::id()).0
+    }
+}
+
+
+mod raydium_amm {
+    use anchor_lang::declare_id;
+
+    declare_id!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+}
+
+#[derive(Clone)]
+pub struct RaydiumAmm;
+
+impl anchor_lang::Id for RaydiumAmm {
+    fn id() -> Pubkey {
+        raydium_amm::id()
+    }
+}
+
+impl RaydiumAmm {
+    // authority of all AMM v4 pools, PDA of the AMM program seeded by "amm authority"
+    pub const AUTHORITY: Pubkey = pubkey!("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1");
+}
+
 
 mod jito_tip_program {
     use anchor_lang::declare_id;
@@ -49,6 +94,25 @@ impl anchor_lang::Id for JitoTipProgram {
     }
 }
 
+impl JitoTipProgram {
+    // the 8 canonical tip payment accounts, PDAs of the Jito tip program
+    // seeded by "TIP_ACCOUNT_0" .. "TIP_ACCOUNT_7"
+    pub const TIP_ACCOUNTS: [Pubkey; 8] = [
+        pubkey!("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5"),
+        pubkey!("HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe"),
+        pubkey!("Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY"),
+        pubkey!("ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49"),
+        pubkey!("DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh"),
+        pubkey!("ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt"),
+        pubkey!("DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL"),
+        pubkey!("3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT"),
+    ];
+
+    pub fn is_tip_account(account: Pubkey) -> bool {
+        Self::TIP_ACCOUNTS.contains(&account)
+    }
+}
+
 
 mod compute_budget {
     use anchor_lang::declare_id;
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    pub new_authority: Signer<'info>, // the pending authority, must sign the transaction
+}
+
+impl AcceptAuthority<'_> {
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+
+        let operator_registry = &mut ctx.accounts.operator_registry;
+        let old_authority = operator_registry.operator_registry_authority;
+        let new_authority = ctx.accounts.new_authority.key();
+
+        operator_registry.accept_registry_authority(new_authority)?;
+
+        emit_cpi!(AuthorityTransferredEvent {
+            old_authority,
+            new_authority,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptPauser<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_PAUSE],
+        bump
+    )]
+    pub pause_registry: Account<'info, PauseRegistry>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    pub new_pauser: Signer<'info>, // the pending pauser, must sign the transaction
+}
+
+impl AcceptPauser<'_> {
+    pub fn accept_pauser(ctx: Context<AcceptPauser>) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+
+        let new_pauser = ctx.accounts.new_pauser.key();
+
+        ctx.accounts.pause_registry.accept_pauser(new_pauser)?;
+
+        emit_cpi!(PauserAddedEvent { pauser: new_pauser });
+
+        Ok(())
+    }
+}
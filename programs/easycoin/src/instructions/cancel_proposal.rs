@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelProposal<'info> {
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [SEED_PREFIX, SEED_PROPOSAL, &proposal.id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        address = proposal.proposer
+    )]
+    pub proposer: SystemAccount<'info>,
+    // approval is checked in handler, other authority signers are passed in remaining accounts
+    pub operator_registry_authority: Signer<'info>,
+}
+
+impl CancelProposal<'_> {
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        ctx.accounts.operator_registry.check_authority_approval(
+            ctx.accounts.operator_registry_authority.key(),
+            ctx.remaining_accounts,
+        )?;
+
+        msg!("proposal {} cancelled", ctx.accounts.proposal.id);
+
+        emit_cpi!(ProposalCancelledEvent {
+            id: ctx.accounts.proposal.id,
+            authority: ctx.accounts.operator_registry_authority.key(),
+        });
+
+        Ok(())
+    }
+}
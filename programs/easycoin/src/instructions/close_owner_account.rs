@@ -1,8 +1,10 @@
 use anchor_lang::prelude::*;
 
 use crate::errors::*;
+use crate::events::*;
 use crate::state::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CloseOwnerAccount<'info> {
     #[account(
@@ -23,7 +25,13 @@ pub struct CloseOwnerAccount<'info> {
 
 impl CloseOwnerAccount<'_> {
     pub fn close_owner_account(ctx: Context<CloseOwnerAccount>) -> Result<()> {
-        require!(ctx.accounts.operator_registry.program_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+
+        emit_cpi!(OwnerAccountClosedEvent {
+            owner: ctx.accounts.owner.key(),
+            owner_account: ctx.accounts.owner_account.key(),
+        });
+
         Ok(())
     }
 }
@@ -16,6 +16,7 @@ pub struct CloseUserTokenAccountArgs {
     pub user_account_nonce: u32,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: CloseUserTokenAccountArgs)]
 pub struct CloseUserTokenAccount<'info> {
@@ -29,10 +30,13 @@ pub struct CloseUserTokenAccount<'info> {
     )]
     pub user_account: SystemAccount<'info>,
     pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
     #[account(
-        constraint = operator_registry.is_operator(operator.key()) @ AgentError::OperatorNotAllowed
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_ACCOUNT_MAINTAINER) @ AgentError::OperatorNotAllowed
     )]
-    pub operator: Signer<'info>,
+    pub operator_account: Account<'info, OperatorAccount>,
     #[account(
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
@@ -48,7 +52,7 @@ impl CloseUserTokenAccount<'_> {
         args: CloseUserTokenAccountArgs,
     ) -> Result<()> {
 
-        require!(ctx.accounts.operator_registry.program_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
 
         let user_token_account_lamports = ctx.accounts.token_account.lamports();
         // if the user token account does not exist, return
@@ -87,9 +91,13 @@ impl CloseUserTokenAccount<'_> {
             signer_seeds,
         ))?;
 
-        emit!(UserTokenAccountClosedEvent {
-            token_account: ctx.accounts.token_account.key(),
+        emit_cpi!(UserTokenAccountClosedEvent {
+            owner_account: owner_account_key,
             user_account: ctx.accounts.user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            token_account: ctx.accounts.token_account.key(),
+            mint: token_account.mint,
             lamports: user_token_account_lamports,
         });
         Ok(())
@@ -15,6 +15,7 @@ pub struct CollectFeeArgs {
     pub only_trade_fee: bool,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: CollectFeeArgs)]
 pub struct CollectFee<'info> {
@@ -26,10 +27,13 @@ pub struct CollectFee<'info> {
     pub user_account: SystemAccount<'info>, // PDA
     #[account(mut)]
     pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
     #[account(
-        constraint = operator_registry.is_operator(operator.key()) @ AgentError::OperatorNotAllowed
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_FEE_COLLECTOR) @ AgentError::OperatorNotAllowed
     )]
-    pub operator: Signer<'info>,
+    pub operator_account: Account<'info, OperatorAccount>,
     #[account(
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
@@ -61,7 +65,8 @@ pub struct CollectFee<'info> {
 impl CollectFee<'_> {
     pub fn collect_fee(ctx: Context<CollectFee>, args: CollectFeeArgs) -> Result<()> {
 
-        require!(ctx.accounts.operator_registry.program_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_FEE_COLLECTION), AgentError::FeaturePaused);
 
         let mut tx_fee = 0;
         if !args.only_trade_fee {
@@ -118,8 +123,11 @@ impl CollectFee<'_> {
             )?;
         }
 
-        emit!(FeeCollectedEvent {
+        emit_cpi!(FeeCollectedEvent {
+            owner_account: owner_account_key,
             user_account: ctx.accounts.user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
             tx_fee,
             trade_fee: due_swap_fee,
         });
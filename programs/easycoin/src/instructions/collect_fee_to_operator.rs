@@ -0,0 +1,180 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::{self},
+    system_program,
+    Discriminator,
+};
+
+use crate::errors::*;
+use crate::state::*;
+use crate::events::*;
+
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct CollectFeeToOperatorArgs {
+    pub user_account_nonce: u32,
+}
+
+// same as CollectFee, but the tx fee is reimbursed to the signing operator
+// instead of a registered tx fee collector
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(args: CollectFeeToOperatorArgs)]
+pub struct CollectFeeToOperator<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_USER, owner_account.key().as_ref(), &args.user_account_nonce.to_le_bytes()],
+        bump
+    )]
+    pub user_account: SystemAccount<'info>, // PDA
+    #[account(mut)]
+    pub owner_account: Account<'info, OwnerAccount>,
+    #[account(mut)]
+    pub operator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_FEE_COLLECTOR) @ AgentError::OperatorNotAllowed
+    )]
+    pub operator_account: Account<'info, OperatorAccount>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    #[account(
+        mut,
+        constraint = fee_registry.is_fee_collector(trade_fee_collector.key()) @ AgentError::FeeCollectorInvalid
+    )]
+    pub trade_fee_collector: SystemAccount<'info>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_FEE],
+        bump
+    )]
+    pub fee_registry: Account<'info, FeeRegistry>,
+    #[account(
+        address = sysvar::instructions::id() // instructions sysvar
+    )]
+    /// CHECK: instructions_sysvar account
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl CollectFeeToOperator<'_> {
+    // count the top-level instructions in this transaction that charge the tx fee:
+    // collect_fee_to_operator, and collect_fee unless only_trade_fee is set
+    fn count_tx_fee_charges_in_transaction(instructions_sysvar: &AccountInfo) -> usize {
+        // discriminator, user_account_nonce, only_trade_fee
+        const ONLY_TRADE_FEE_OFFSET: usize = 8 + 4;
+
+        let mut charges = 0;
+        let mut index = 0;
+        while let Ok(instruction) =
+            sysvar::instructions::load_instruction_at_checked(index, instructions_sysvar)
+        {
+            if instruction.program_id == crate::ID
+                && (instruction
+                    .data
+                    .starts_with(&crate::instruction::CollectFeeToOperator::DISCRIMINATOR)
+                    || (instruction
+                        .data
+                        .starts_with(&crate::instruction::CollectFee::DISCRIMINATOR)
+                        && instruction.data.get(ONLY_TRADE_FEE_OFFSET) == Some(&0)))
+            {
+                charges += 1;
+            }
+            index += 1;
+        }
+        charges
+    }
+
+    pub fn collect_fee_to_operator(ctx: Context<CollectFeeToOperator>, args: CollectFeeToOperatorArgs) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_FEE_COLLECTION), AgentError::FeaturePaused);
+
+        // the tx fee is paid once per transaction, so it is reimbursed at most once
+        let charges = Self::count_tx_fee_charges_in_transaction(
+            ctx.accounts.instructions_sysvar.to_account_info().as_ref(),
+        );
+        require!(charges <= 1, AgentError::TxFeeAlreadyInTransaction);
+
+        let tx_fee = FeeRegistry::calculate_tx_fee(
+            ctx.accounts.instructions_sysvar.to_account_info().as_ref(),
+        )?;
+
+        let owner_account = &mut ctx.accounts.owner_account;
+        let due_swap_fee = owner_account.get_user_account_due_fee(args.user_account_nonce)?;
+        owner_account.sub_user_account_due_fee(args.user_account_nonce, due_swap_fee)?;
+
+        // reimburse as much of the tx fee as the user account can spare,
+        // the rest is recorded as unrecovered in the operator ledger
+        let required_rent = Rent::get()?.minimum_balance(ctx.accounts.user_account.data_len());
+        let user_account_balance = ctx
+            .accounts
+            .user_account
+            .lamports()
+            .saturating_sub(required_rent)
+            .saturating_sub(due_swap_fee);
+        let reimbursed = tx_fee.min(user_account_balance);
+
+        let owner_account_key = ctx.accounts.owner_account.key();
+        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
+        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEED_PREFIX,
+            SEED_USER,
+            owner_account_key.as_ref(),
+            user_account_nonce_bytes.as_ref(),
+            user_account_bump_bytes.as_ref(),
+        ]];
+
+        #[cfg(feature = "enable-log")]
+        msg!("collect_fee_to_operator: tx_fee={} reimbursed={} due_fee={}", tx_fee, reimbursed, due_swap_fee);
+
+        // collect trade fee
+        if due_swap_fee > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user_account.to_account_info(),
+                        to: ctx.accounts.trade_fee_collector.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                due_swap_fee,
+            )?;
+        }
+
+        if reimbursed > 0 {
+            // reimburse tx fee to operator
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user_account.to_account_info(),
+                        to: ctx.accounts.operator.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                reimbursed,
+            )?;
+        }
+
+        ctx.accounts.operator_account.record_tx_fee(tx_fee, reimbursed)?;
+
+        emit_cpi!(OperatorFeeReimbursedEvent {
+            owner_account: owner_account_key,
+            user_account: ctx.accounts.user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            tx_fee,
+            reimbursed,
+            trade_fee: due_swap_fee,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+// move platform fee taken by Jupiter routes out of a fee token account of the fee registry
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectPlatformFee<'info> {
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = fee_registry,
+        token::token_program = token_program,
+    )]
+    pub platform_fee_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        constraint = fee_registry.is_fee_collector(destination_token_account.owner) @ AgentError::FeeCollectorInvalid
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub operator: Signer<'info>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_FEE_COLLECTOR) @ AgentError::OperatorNotAllowed
+    )]
+    pub operator_account: Account<'info, OperatorAccount>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_FEE],
+        bump = fee_registry.bump
+    )]
+    pub fee_registry: Account<'info, FeeRegistry>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl CollectPlatformFee<'_> {
+    pub fn collect_platform_fee(ctx: Context<CollectPlatformFee>) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_FEE_COLLECTION), AgentError::FeaturePaused);
+
+        let amount = ctx.accounts.platform_fee_account.amount;
+        let fee_registry_bump_bytes = ctx.accounts.fee_registry.bump.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEED_PREFIX,
+            SEED_FEE,
+            fee_registry_bump_bytes.as_ref(),
+        ]];
+
+        #[cfg(feature = "enable-log")]
+        msg!("collect_platform_fee: amount={}", amount);
+
+        if amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token_interface::TransferChecked {
+                        from: ctx.accounts.platform_fee_account.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.destination_token_account.to_account_info(),
+                        authority: ctx.accounts.fee_registry.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                amount,
+                ctx.accounts.mint.decimals,
+            )?;
+        }
+
+        emit_cpi!(PlatformFeeCollectedEvent {
+            operator: ctx.accounts.operator.key(),
+            platform_fee_account: ctx.accounts.platform_fee_account.key(),
+            fee_collector: ctx.accounts.destination_token_account.owner,
+            destination_token_account: ctx.accounts.destination_token_account.key(),
+            mint: ctx.accounts.mint.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+}
@@ -2,8 +2,10 @@ use anchor_lang::prelude::*;
 
 use crate::state::*;
 use crate::errors::*;
+use crate::events::*;
 
 /* Create Owner Account */
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreateOwnerAccount<'info> {
     #[account(
@@ -31,11 +33,17 @@ impl CreateOwnerAccount<'_> {
     pub fn create_owner_account(ctx: Context<Self>) -> Result<()> {
 
         require!(ctx.accounts.operator_registry.program_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_ACCOUNT_CREATION), AgentError::FeaturePaused);
 
         let owner_account = &mut ctx.accounts.owner_account;
         owner_account.bump = ctx.bumps.owner_account;
         owner_account.created_user_accounts = vec![];
-        
+
+        emit_cpi!(OwnerAccountCreatedEvent {
+            owner: ctx.accounts.owner.key(),
+            owner_account: ctx.accounts.owner_account.key(),
+        });
+
         Ok(())
     }
 
@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 
 use crate::state::*;
 use crate::errors::*;
+use crate::events::*;
 
 /* Create User Account */
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -10,6 +11,7 @@ pub struct CreateUserAccountArgs {
 }
 
 /* Create User Account */
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: CreateUserAccountArgs)]
 pub struct CreateUserAccount<'info> {
@@ -45,8 +47,18 @@ impl CreateUserAccount<'_> {
     pub fn create_user_account(ctx: Context<CreateUserAccount>, args: CreateUserAccountArgs) -> Result<()> {
 
         require!(ctx.accounts.operator_registry.program_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_ACCOUNT_CREATION), AgentError::FeaturePaused);
 
         /* record info to owner account */
-        ctx.accounts.owner_account.add_user_account(args.nonce)
+        ctx.accounts.owner_account.add_user_account(args.nonce)?;
+
+        emit_cpi!(UserAccountCreatedEvent {
+            owner: ctx.accounts.owner.key(),
+            owner_account: ctx.accounts.owner_account.key(),
+            user_account: ctx.accounts.user_account.key(),
+            nonce: args.nonce,
+        });
+
+        Ok(())
     }   
 }
\ No newline at end of file
@@ -5,6 +5,7 @@ use anchor_spl::{
 };
 
 use crate::errors::*;
+use crate::events::*;
 use crate::state::*;
 
 #[derive(AnchorDeserialize, AnchorSerialize)]
@@ -12,6 +13,7 @@ pub struct CreateUserTokenAccountArgs {
     pub user_account_nonce: u32,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: CreateUserTokenAccountArgs)]
 pub struct CreateUserTokenAccount<'info> {
@@ -29,10 +31,13 @@ pub struct CreateUserTokenAccount<'info> {
     )]
     pub user_account: SystemAccount<'info>,
     pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
     #[account(
-        constraint = operator_registry.is_operator(operator.key()) @ AgentError::OperatorNotAllowed
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_ACCOUNT_MAINTAINER) @ AgentError::OperatorNotAllowed
     )]
-    pub operator: Signer<'info>,
+    pub operator_account: Account<'info, OperatorAccount>,
     #[account(
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
@@ -50,6 +55,7 @@ impl CreateUserTokenAccount<'_> {
     ) -> Result<()> {
 
         require!(ctx.accounts.operator_registry.program_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_ACCOUNT_CREATION), AgentError::FeaturePaused);
 
         let user_account = &mut ctx.accounts.user_account;
 
@@ -81,6 +87,17 @@ impl CreateUserTokenAccount<'_> {
                 token_program: ctx.accounts.token_program.to_account_info(),
             },
             signer_seeds,
-        ))
+        ))?;
+
+        emit_cpi!(UserTokenAccountCreatedEvent {
+            owner_account: owner_account_key,
+            user_account: ctx.accounts.user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            token_account: ctx.accounts.token_account.key(),
+            mint: ctx.accounts.mint.key(),
+        });
+
+        Ok(())
     }
 }
@@ -0,0 +1,401 @@
+use anchor_lang::{prelude::*, system_program};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::instructions::{ManageFeeOp, ManageOp};
+use crate::state::*;
+
+// anyone can execute a proposal once its eta has passed,
+// operator proposals take the operator account PDAs in remaining accounts,
+// in the same order as the proposal's operators
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    // registries created with fewer fees are grown by SetFee, see grow_fee_registry
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_FEE],
+        bump
+    )]
+    pub fee_registry: Account<'info, FeeRegistry>,
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_PAUSE],
+        bump
+    )]
+    pub pause_registry: Account<'info, PauseRegistry>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [SEED_PREFIX, SEED_PROPOSAL, &proposal.id.to_le_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    #[account(
+        mut,
+        address = proposal.proposer
+    )]
+    pub proposer: SystemAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>, // pays rent of new operator accounts and of a grown fee registry
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExecuteProposal<'info> {
+    pub(crate) fn operator_accounts(
+        remaining_accounts: &'info [AccountInfo<'info>],
+        operators: &[Pubkey],
+    ) -> Result<Vec<(Pubkey, u8, &'info AccountInfo<'info>)>> {
+        require!(
+            remaining_accounts.len() >= operators.len(),
+            AgentError::OperatorAccountInvalid
+        );
+
+        operators
+            .iter()
+            .zip(remaining_accounts.iter())
+            .map(|(operator, operator_account)| {
+                let (address, bump) = Pubkey::find_program_address(
+                    &[SEED_PREFIX, SEED_OPERATOR, operator.as_ref()],
+                    &crate::ID,
+                );
+                if operator_account.key() != address {
+                    msg!("operator account invalid: {} for {}", operator_account.key(), operator);
+                    return err!(AgentError::OperatorAccountInvalid);
+                }
+                Ok((*operator, bump, operator_account))
+            })
+            .collect()
+    }
+
+    // shared with migrate_operator_registry
+    pub(crate) fn create_operator_account(
+        payer: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        operator_account: &AccountInfo<'info>,
+        operator: Pubkey,
+        bump: u8,
+    ) -> Result<()> {
+        let space = OperatorAccount::size();
+        let rent = Rent::get()?.minimum_balance(space);
+        let signer_seeds: &[&[&[u8]]] = &[&[SEED_PREFIX, SEED_OPERATOR, operator.as_ref(), &[bump]]];
+
+        let lamports = operator_account.lamports();
+        if lamports == 0 {
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program,
+                    system_program::CreateAccount {
+                        from: payer,
+                        to: operator_account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                rent,
+                space as u64,
+                &crate::ID,
+            )?;
+        } else {
+            // the address may have been funded in advance
+            if lamports < rent {
+                system_program::transfer(
+                    CpiContext::new(
+                        system_program.clone(),
+                        system_program::Transfer {
+                            from: payer,
+                            to: operator_account.clone(),
+                        },
+                    ),
+                    rent - lamports,
+                )?;
+            }
+            system_program::allocate(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    system_program::Allocate {
+                        account_to_allocate: operator_account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                space as u64,
+            )?;
+            system_program::assign(
+                CpiContext::new_with_signer(
+                    system_program,
+                    system_program::Assign {
+                        account_to_assign: operator_account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                &crate::ID,
+            )?;
+        }
+
+        let account = OperatorAccount {
+            bump,
+            operator,
+            ..Default::default()
+        };
+        let mut data = operator_account.try_borrow_mut_data()?;
+        account.try_serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+
+    // grows a fee registry created before the caps to the current size, so caps can be appended
+    fn grow_fee_registry(
+        payer: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        fee_registry: AccountInfo<'info>,
+    ) -> Result<()> {
+        let space = FeeRegistry::size();
+        if fee_registry.data_len() >= space {
+            return Ok(());
+        }
+
+        let rent = Rent::get()?.minimum_balance(space);
+        let lamports = fee_registry.lamports();
+        if lamports < rent {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program,
+                    system_program::Transfer {
+                        from: payer,
+                        to: fee_registry.clone(),
+                    },
+                ),
+                rent - lamports,
+            )?;
+        }
+        fee_registry.realloc(space, false)?;
+        msg!("fee registry grown to {} bytes", space);
+        Ok(())
+    }
+
+    pub(crate) fn load_operator_account(
+        operator_account: &'info AccountInfo<'info>,
+        operator: Pubkey,
+    ) -> Result<Account<'info, OperatorAccount>> {
+        if operator_account.owner != &crate::ID {
+            msg!("operator not registered before: {}", operator);
+            return err!(AgentError::OperatorNotAllowed);
+        }
+        let account = Account::<OperatorAccount>::try_from(operator_account)?;
+        require_keys_eq!(account.operator, operator, AgentError::OperatorAccountInvalid);
+        Ok(account)
+    }
+
+    pub fn execute_proposal(ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+
+        let now = Clock::get()?.unix_timestamp;
+        if !ctx.accounts.proposal.is_executable(now) {
+            msg!("proposal {} not ready, eta {}", ctx.accounts.proposal.id, ctx.accounts.proposal.eta);
+            return err!(AgentError::ProposalNotReady);
+        }
+
+        let id = ctx.accounts.proposal.id;
+        let authority = ctx.accounts.proposal.proposer;
+        let operator_registry = &mut ctx.accounts.operator_registry;
+
+        match ctx.accounts.proposal.action.clone() {
+            ProposalAction::Manage { op } => match op {
+                ManageOp::ProposeAuthority { new_authority } => {
+                    operator_registry.propose_registry_authority(new_authority)?;
+                    emit_cpi!(AuthorityTransferProposedEvent {
+                        authority,
+                        pending_authority: new_authority,
+                    });
+                }
+                ManageOp::CancelAuthorityTransfer => {
+                    let pending_authority = operator_registry.pending_authority;
+                    operator_registry.cancel_registry_authority_transfer()?;
+                    emit_cpi!(AuthorityTransferCancelledEvent {
+                        authority,
+                        pending_authority,
+                    });
+                }
+                ManageOp::AddOperator { operators, roles } => {
+                    require!(OperatorAccount::is_legal_roles(roles), AgentError::OperatorRolesInvalid);
+
+                    for (operator, bump, operator_account) in
+                        Self::operator_accounts(ctx.remaining_accounts, &operators)?
+                    {
+                        if operator_account.owner == &crate::ID {
+                            msg!("operator already registered before: {}", operator);
+                            continue;
+                        }
+
+                        Self::create_operator_account(
+                            ctx.accounts.payer.to_account_info(),
+                            ctx.accounts.system_program.to_account_info(),
+                            operator_account,
+                            operator,
+                            bump,
+                        )?;
+                        let mut account = Self::load_operator_account(operator_account, operator)?;
+                        account.set_roles(roles)?;
+                        account.exit(&crate::ID)?;
+                        msg!("operator added: {}, roles: {:#04x}", operator, roles);
+
+                        emit_cpi!(OperatorAddedEvent {
+                            authority,
+                            operator,
+                            roles,
+                        });
+                    }
+                }
+                ManageOp::RemoveOperator { operators } => {
+                    for (operator, _, operator_account) in
+                        Self::operator_accounts(ctx.remaining_accounts, &operators)?
+                    {
+                        if operator_account.owner != &crate::ID {
+                            msg!("operator not registered before: {}", operator);
+                            continue;
+                        }
+
+                        let account = Self::load_operator_account(operator_account, operator)?;
+                        account.close(ctx.accounts.proposer.to_account_info())?;
+                        msg!("operator removed: {}", operator);
+
+                        emit_cpi!(OperatorRemovedEvent {
+                            authority,
+                            operator,
+                        });
+                    }
+                }
+                ManageOp::SetOperatorRoles { operators, roles } => {
+                    for (operator, _, operator_account) in
+                        Self::operator_accounts(ctx.remaining_accounts, &operators)?
+                    {
+                        let mut account = Self::load_operator_account(operator_account, operator)?;
+                        account.set_roles(roles)?;
+                        account.exit(&crate::ID)?;
+
+                        emit_cpi!(OperatorRolesSetEvent {
+                            authority,
+                            operator,
+                            roles,
+                        });
+                    }
+                }
+                ManageOp::SetOperatorQuota {
+                    operators,
+                    expires_at,
+                    max_volume_per_epoch,
+                } => {
+                    for (operator, _, operator_account) in
+                        Self::operator_accounts(ctx.remaining_accounts, &operators)?
+                    {
+                        let mut account = Self::load_operator_account(operator_account, operator)?;
+                        account.set_quota(expires_at, max_volume_per_epoch)?;
+                        account.exit(&crate::ID)?;
+
+                        emit_cpi!(OperatorQuotaSetEvent {
+                            authority,
+                            operator,
+                            expires_at,
+                            max_volume_per_epoch,
+                        });
+                    }
+                }
+                ManageOp::SetAuthoritySigners { signers, threshold } => {
+                    operator_registry.set_authority_signers(signers.clone(), threshold)?;
+                    emit_cpi!(AuthoritySignersSetEvent { signers, threshold });
+                }
+                ManageOp::SetTimelockDelay { delay } => {
+                    let old_delay = operator_registry.timelock_delay;
+                    operator_registry.set_timelock_delay(delay)?;
+                    emit_cpi!(TimelockDelaySetEvent {
+                        old_delay,
+                        new_delay: delay,
+                    });
+                }
+                ManageOp::SetMaxPauseDuration { duration } => {
+                    let old_duration = operator_registry.max_pause_duration;
+                    operator_registry.set_max_pause_duration(duration)?;
+                    emit_cpi!(MaxPauseDurationSetEvent {
+                        old_duration,
+                        new_duration: duration,
+                    });
+                }
+                ManageOp::WindDown => {
+                    operator_registry.wind_down()?;
+                    emit_cpi!(ProgramWindDownEvent { authority });
+                }
+            },
+            ProposalAction::ManageFee { op } => {
+                if matches!(op, ManageFeeOp::SetFee { .. }) {
+                    Self::grow_fee_registry(
+                        ctx.accounts.payer.to_account_info(),
+                        ctx.accounts.system_program.to_account_info(),
+                        ctx.accounts.fee_registry.to_account_info(),
+                    )?;
+                }
+                let fees = &mut ctx.accounts.fee_registry;
+
+                match op {
+                    ManageFeeOp::SetFee { fees_to_set } => {
+                        for fee in fees_to_set.iter() {
+                            fees.set_fee_value(fee.fee_index, fee.value)?;
+                            emit_cpi!(FeeSetEvent {
+                                authority,
+                                fee_index: fee.fee_index as u8,
+                                value: fee.value,
+                            });
+                        }
+                    }
+                    ManageFeeOp::AddFeeCollector { fee_collectors } => {
+                        for fee_collector in fee_collectors.iter() {
+                            fees.add_fee_collector(*fee_collector)?;
+                            emit_cpi!(FeeCollectorAddedEvent {
+                                authority,
+                                fee_collector: *fee_collector,
+                            });
+                        }
+                    }
+                    ManageFeeOp::RemoveFeeCollector { fee_collectors } => {
+                        for fee_collector in fee_collectors.iter() {
+                            fees.remove_fee_collector(*fee_collector)?;
+                            emit_cpi!(FeeCollectorRemovedEvent {
+                                authority,
+                                fee_collector: *fee_collector,
+                            });
+                        }
+                    }
+                }
+            }
+            ProposalAction::AddPauser { pauser } => {
+                ctx.accounts.pause_registry.propose_pauser(pauser)?;
+                emit_cpi!(PauserTransferProposedEvent {
+                    authority,
+                    pending_pauser: pauser,
+                });
+            }
+            ProposalAction::CancelPauserTransfer => {
+                let pending_pauser = ctx.accounts.pause_registry.pending_pauser;
+                ctx.accounts.pause_registry.cancel_pauser_transfer()?;
+                emit_cpi!(PauserTransferCancelledEvent {
+                    authority,
+                    pending_pauser,
+                });
+            }
+            ProposalAction::RemovePauser { pauser } => {
+                ctx.accounts.pause_registry.remove_pauser(pauser)?;
+                emit_cpi!(PauserRemovedEvent { authority, pauser });
+            }
+        }
+
+        msg!("proposal {} executed", id);
+
+        emit_cpi!(ProposalExecutedEvent { id });
+
+        Ok(())
+    }
+}
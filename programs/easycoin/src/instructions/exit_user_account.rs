@@ -0,0 +1,272 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken},
+    token::Token,
+    token_2022::Token2022,
+    token_interface::{self, Mint, TokenAccount},
+};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct ExitUserAccountArgs {
+    pub user_account_nonce: u32,
+}
+
+// token accounts are passed in remaining accounts as
+// [token_account, mint, owner_token_account] triples,
+// owner_token_account is the owner's ATA of mint, created if it does not exist,
+// each triple is handled by the token program owning its token account
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(args: ExitUserAccountArgs)]
+pub struct ExitUserAccount<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_USER, owner_account.key().as_ref(), &args.user_account_nonce.to_le_bytes()],
+        bump
+    )]
+    pub user_account: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_OWNER, owner.key().as_ref()],
+        bump
+    )]
+    pub owner_account: Account<'info, OwnerAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>, // pays rent of owner token accounts
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    // due fee is settled to a registered fee collector before exit,
+    // only required when the user account has due fee
+    #[account(
+        mut,
+        constraint = fee_registry.is_fee_collector(trade_fee_collector.key()) @ AgentError::FeeCollectorInvalid
+    )]
+    pub trade_fee_collector: Option<SystemAccount<'info>>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_FEE],
+        bump
+    )]
+    pub fee_registry: Account<'info, FeeRegistry>,
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExitUserAccount<'info> {
+    fn token_program_of(&self, token_account_info: &AccountInfo<'info>) -> Result<AccountInfo<'info>> {
+        if token_account_info.owner == &Token::id() {
+            Ok(self.token_program.to_account_info())
+        } else if token_account_info.owner == &Token2022::id() {
+            Ok(self.token_2022_program.to_account_info())
+        } else {
+            msg!("token account {} not owned by a token program", token_account_info.key());
+            err!(AgentError::UserTokenAccountInvalid)
+        }
+    }
+
+    pub fn exit_user_account(
+        ctx: Context<'_, '_, 'info, 'info, ExitUserAccount<'info>>,
+        args: ExitUserAccountArgs,
+    ) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_WITHDRAWAL), AgentError::FeaturePaused);
+        require!(ctx.remaining_accounts.len() % 3 == 0, AgentError::UserTokenAccountInvalid);
+
+        let owner_key = ctx.accounts.owner.key();
+        let owner_account_key = ctx.accounts.owner_account.key();
+        let user_account_key = ctx.accounts.user_account.key();
+        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
+        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEED_PREFIX,
+            SEED_USER,
+            owner_account_key.as_ref(),
+            user_account_nonce_bytes.as_ref(),
+            user_account_bump_bytes.as_ref(),
+        ]];
+
+        // move token balances to the owner and close token accounts
+        for accounts in ctx.remaining_accounts.chunks(3) {
+            let (token_account_info, mint_info, owner_token_account_info) =
+                (&accounts[0], &accounts[1], &accounts[2]);
+
+            let token_program = ctx.accounts.token_program_of(token_account_info)?;
+
+            let token_account = InterfaceAccount::<TokenAccount>::try_from(token_account_info)?;
+            if token_account.owner != user_account_key || token_account.mint != mint_info.key() {
+                msg!("token account {} invalid", token_account_info.key());
+                return err!(AgentError::UserTokenAccountInvalid);
+            }
+
+            // native WSOL is unwrapped by closing, lamports are withdrawn below
+            if !token_account.is_native() && token_account.amount > 0 {
+                let owner_token_account_key = get_associated_token_address_with_program_id(
+                    &owner_key,
+                    &token_account.mint,
+                    token_program.key,
+                );
+                if owner_token_account_info.key() != owner_token_account_key {
+                    msg!("owner token account {} invalid", owner_token_account_info.key());
+                    return err!(AgentError::UserTokenAccountInvalid);
+                }
+
+                associated_token::create_idempotent(CpiContext::new(
+                    ctx.accounts.associated_token_program.to_account_info(),
+                    associated_token::Create {
+                        payer: ctx.accounts.owner.to_account_info(),
+                        associated_token: owner_token_account_info.clone(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                        mint: mint_info.clone(),
+                        system_program: ctx.accounts.system_program.to_account_info(),
+                        token_program: token_program.clone(),
+                    },
+                ))?;
+
+                let mint = InterfaceAccount::<Mint>::try_from(mint_info)?;
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        token_program.clone(),
+                        token_interface::TransferChecked {
+                            from: token_account_info.clone(),
+                            mint: mint_info.clone(),
+                            to: owner_token_account_info.clone(),
+                            authority: ctx.accounts.user_account.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    token_account.amount,
+                    mint.decimals,
+                )?;
+
+                emit_cpi!(TokenWithdrawEvent {
+                    owner: owner_key,
+                    owner_account: owner_account_key,
+                    user_account: user_account_key,
+                    nonce: args.user_account_nonce,
+                    token_account: token_account_info.key(),
+                    mint: token_account.mint,
+                    amount: token_account.amount,
+                });
+            }
+
+            let lamports = token_account_info.lamports();
+
+            token_interface::close_account(CpiContext::new_with_signer(
+                token_program,
+                token_interface::CloseAccount {
+                    account: token_account_info.clone(),
+                    destination: ctx.accounts.user_account.to_account_info(),
+                    authority: ctx.accounts.user_account.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+
+            emit_cpi!(OwnerTokenAccountClosedEvent {
+                owner: owner_key,
+                owner_account: owner_account_key,
+                user_account: user_account_key,
+                nonce: args.user_account_nonce,
+                token_account: token_account_info.key(),
+                mint: token_account.mint,
+                lamports,
+            });
+        }
+
+        // settle due fee, capped by the balance so the owner can always exit,
+        // the unpaid remainder is written off with the closed user account, see README
+        let owner_account = &mut ctx.accounts.owner_account;
+        let due_fee = owner_account.get_user_account_due_fee(args.user_account_nonce)?;
+        if due_fee > 0 {
+            let trade_fee_collector = ctx
+                .accounts
+                .trade_fee_collector
+                .as_ref()
+                .ok_or(AgentError::FeeCollectorMissing)?;
+            let settled_fee = due_fee.min(ctx.accounts.user_account.lamports());
+            owner_account.sub_user_account_due_fee(args.user_account_nonce, due_fee)?;
+
+            if settled_fee > 0 {
+                system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.user_account.to_account_info(),
+                            to: trade_fee_collector.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    settled_fee,
+                )?;
+            }
+
+            emit_cpi!(DueFeeSettledEvent {
+                owner: owner_key,
+                owner_account: owner_account_key,
+                user_account: user_account_key,
+                nonce: args.user_account_nonce,
+                fee_collector: trade_fee_collector.key(),
+                due_fee,
+                settled_fee,
+            });
+        }
+
+        // withdraw all lamports to owner
+        let withdraw_amount = ctx.accounts.user_account.lamports();
+        if withdraw_amount > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.user_account.to_account_info(),
+                        to: ctx.accounts.owner.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                withdraw_amount,
+            )?;
+        }
+
+        emit_cpi!(WithdrawEvent {
+            owner: owner_key,
+            owner_account: owner_account_key,
+            user_account: user_account_key,
+            nonce: args.user_account_nonce,
+            amount: withdraw_amount,
+        });
+
+        ctx.accounts
+            .owner_account
+            .remove_user_account(args.user_account_nonce)?;
+
+        emit_cpi!(UserAccountClosedEvent {
+            owner: owner_key,
+            owner_account: owner_account_key,
+            user_account: user_account_key,
+            nonce: args.user_account_nonce,
+        });
+
+        // close owner account with its last user account
+        if ctx.accounts.owner_account.is_eglible_to_close() {
+            ctx.accounts
+                .owner_account
+                .close(ctx.accounts.owner.to_account_info())?;
+
+            emit_cpi!(OwnerAccountClosedEvent {
+                owner: owner_key,
+                owner_account: owner_account_key,
+            });
+        }
+
+        Ok(())
+    }
+}
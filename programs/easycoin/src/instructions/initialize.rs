@@ -1,25 +1,67 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::bpf_loader_upgradeable};
 
 use crate::errors::*;
+use crate::events::*;
 use crate::state::*;
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub struct InitializeArgs {
+    pub operator_registry_authority: Pubkey,
+    pub pauser: Pubkey,
+    pub swap_fee_numerator: u64,
+    pub swap_fee_denominator: u64,
+    pub max_slippage_bps: u64,
+    pub max_platform_fee_bps: u64,
+    pub timelock_delay: i64, // seconds between queueing and executing a proposal, 72 hours on mainnet
+}
+
+// only the program upgrade authority can initialize, so a fresh deploy can not be front-run
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
         init,
-        payer = payer,
+        payer = upgrade_authority,
         space = OperatorRegistry::size(),
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
     )]
     pub operator_registry: Account<'info, OperatorRegistry>,
+    #[account(
+        init,
+        payer = upgrade_authority,
+        space = FeeRegistry::size(),
+        seeds = [SEED_PREFIX, SEED_FEE],
+        bump
+    )]
+    pub fee_registry: Account<'info, FeeRegistry>,
+    #[account(
+        init,
+        payer = upgrade_authority,
+        space = PauseRegistry::size(),
+        seeds = [SEED_PREFIX, SEED_PAUSE],
+        bump
+    )]
+    pub pause_registry: Account<'info, PauseRegistry>,
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ AgentError::UpgradeAuthorityNotAllowed
+    )]
+    pub program_data: Account<'info, ProgramData>,
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub upgrade_authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 impl Initialize<'_> {
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>, args: InitializeArgs) -> Result<()> {
+        require!(
+            args.operator_registry_authority != Pubkey::default() && args.pauser != Pubkey::default(),
+            AgentError::InitializeArgsInvalid
+        );
+
         let operator_registry = &mut ctx.accounts.operator_registry;
 
         if operator_registry.program_status != ProgramStatus::Uninitialized {
@@ -27,17 +69,73 @@ impl Initialize<'_> {
         }
 
         operator_registry.bump = ctx.bumps.operator_registry;
-        operator_registry.operators = vec![];
-        operator_registry.operator_registry_authority =
-            OperatorRegistry::default_registry_authority();
+        operator_registry.authority_signers = vec![];
+        operator_registry.authority_threshold = 0;
+        operator_registry.pending_authority = Pubkey::default();
+        operator_registry.set_timelock_delay(args.timelock_delay)?;
+        operator_registry.proposal_count = 0;
+        operator_registry.paused_features = 0;
+        operator_registry.paused_until = 0;
+        operator_registry.features_paused_until = 0;
+        operator_registry.max_pause_duration = OperatorRegistry::default_max_pause_duration();
+        operator_registry.operator_registry_authority = args.operator_registry_authority;
 
         msg!(
-            "default operator_registry_authority: {}",
+            "operator_registry_authority: {}",
             operator_registry.operator_registry_authority.to_string()
         );
 
         operator_registry.program_status = ProgramStatus::Initialized;
 
+        let fee_registry = &mut ctx.accounts.fee_registry;
+
+        fee_registry.bump = ctx.bumps.fee_registry;
+        fee_registry.fees = vec![0; FeeRegistry::FEE_INDEX_LEN];
+        fee_registry.fee_collectors = vec![];
+
+        fee_registry.set_fee_value(FeeIndex::SwapFeeNumerator, args.swap_fee_numerator)?;
+        fee_registry.set_fee_value(FeeIndex::SwapFeeDenominator, args.swap_fee_denominator)?;
+        fee_registry.set_fee_value(FeeIndex::MaxSlippageBps, args.max_slippage_bps)?;
+        fee_registry.set_fee_value(FeeIndex::MaxPlatformFeeBps, args.max_platform_fee_bps)?;
+
+        let pause_registry = &mut ctx.accounts.pause_registry;
+
+        pause_registry.bump = ctx.bumps.pause_registry;
+        pause_registry.pausers = vec![args.pauser];
+        pause_registry.pending_pauser = Pubkey::default();
+
+        let authority = args.operator_registry_authority;
+        emit_cpi!(ProgramInitializedEvent {
+            operator_registry_authority: authority,
+        });
+        emit_cpi!(FeeSetEvent {
+            authority,
+            fee_index: FeeIndex::SwapFeeNumerator as u8,
+            value: args.swap_fee_numerator,
+        });
+        emit_cpi!(FeeSetEvent {
+            authority,
+            fee_index: FeeIndex::SwapFeeDenominator as u8,
+            value: args.swap_fee_denominator,
+        });
+        emit_cpi!(FeeSetEvent {
+            authority,
+            fee_index: FeeIndex::MaxSlippageBps as u8,
+            value: args.max_slippage_bps,
+        });
+        emit_cpi!(FeeSetEvent {
+            authority,
+            fee_index: FeeIndex::MaxPlatformFeeBps as u8,
+            value: args.max_platform_fee_bps,
+        });
+        emit_cpi!(TimelockDelaySetEvent {
+            old_delay: 0,
+            new_delay: args.timelock_delay,
+        });
+        emit_cpi!(PauserAddedEvent {
+            pauser: args.pauser,
+        });
+
         Ok(())
     }
 }
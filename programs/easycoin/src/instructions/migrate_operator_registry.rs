@@ -0,0 +1,127 @@
+use anchor_lang::{prelude::*, Discriminator};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::instructions::ExecuteProposal;
+use crate::state::*;
+
+// rewrites a registry created before the governance fields were added, see LegacyOperatorRegistry,
+// each legacy operator gets an operator account PDA with all roles,
+// operator account PDAs are passed in remaining accounts, in the same order as the legacy operators
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateOperatorRegistry<'info> {
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    /// CHECK: legacy operator registry, deserialized in handler
+    pub operator_registry: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub operator_registry_authority: Signer<'info>, // pays rent of operator accounts
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateOperatorRegistry<'info> {
+    fn load_legacy_registry(operator_registry: &AccountInfo) -> Result<LegacyOperatorRegistry> {
+        let data = operator_registry.try_borrow_data()?;
+        // legacy and current registries differ in size, a migrated registry can not be migrated again
+        if data.len() != LegacyOperatorRegistry::size()
+            || !data.starts_with(&OperatorRegistry::DISCRIMINATOR)
+        {
+            msg!("operator registry is not in legacy layout, size {}", data.len());
+            return err!(AgentError::RegistryMigrationInvalid);
+        }
+
+        LegacyOperatorRegistry::deserialize(&mut &data[8..])
+            .map_err(|_| error!(AgentError::RegistryMigrationInvalid))
+    }
+
+    pub fn migrate_operator_registry(
+        ctx: Context<'_, '_, 'info, 'info, MigrateOperatorRegistry<'info>>,
+    ) -> Result<()> {
+        let operator_registry_info = ctx.accounts.operator_registry.to_account_info();
+        let authority = ctx.accounts.operator_registry_authority.key();
+
+        let legacy = Self::load_legacy_registry(&operator_registry_info)?;
+        require_keys_eq!(
+            legacy.operator_registry_authority,
+            authority,
+            AgentError::OperatorRegistryAuthorityNotAllowed
+        );
+
+        // legacy operators could do everything an operator can do
+        for (operator, bump, operator_account) in
+            ExecuteProposal::operator_accounts(ctx.remaining_accounts, &legacy.operators)?
+        {
+            if operator_account.owner == &crate::ID {
+                msg!("operator already registered before: {}", operator);
+                continue;
+            }
+
+            ExecuteProposal::create_operator_account(
+                ctx.accounts.operator_registry_authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                operator_account,
+                operator,
+                bump,
+            )?;
+            let mut account = ExecuteProposal::load_operator_account(operator_account, operator)?;
+            account.set_roles(ROLE_ALL)?;
+            account.exit(&crate::ID)?;
+
+            emit_cpi!(OperatorAddedEvent {
+                authority,
+                operator,
+                roles: ROLE_ALL,
+            });
+        }
+
+        let paused = legacy.program_status == ProgramStatus::Paused;
+        let operator_registry = OperatorRegistry {
+            bump: legacy.bump,
+            program_status: legacy.program_status,
+            operator_registry_authority: legacy.operator_registry_authority,
+            authority_signers: vec![],
+            authority_threshold: 0,
+            pending_authority: Pubkey::default(),
+            timelock_delay: OperatorRegistry::default_timelock_delay(),
+            proposal_count: 0,
+            paused_features: 0,
+            // legacy pauses have no expiry, they last until the authority unpauses
+            paused_until: if paused { i64::MAX } else { 0 },
+            max_pause_duration: OperatorRegistry::default_max_pause_duration(),
+            features_paused_until: 0,
+        };
+
+        // the current layout is smaller, rent in excess is returned to the authority
+        operator_registry_info.realloc(OperatorRegistry::size(), false)?;
+        let rent = Rent::get()?.minimum_balance(OperatorRegistry::size());
+        let excess = operator_registry_info.lamports().saturating_sub(rent);
+        if excess > 0 {
+            **operator_registry_info.try_borrow_mut_lamports()? -= excess;
+            **ctx
+                .accounts
+                .operator_registry_authority
+                .to_account_info()
+                .try_borrow_mut_lamports()? += excess;
+        }
+
+        let mut data = operator_registry_info.try_borrow_mut_data()?;
+        operator_registry.try_serialize(&mut &mut data[..])?;
+
+        msg!(
+            "operator registry migrated, {} operators",
+            legacy.operators.len()
+        );
+
+        emit_cpi!(RegistryMigratedEvent {
+            authority,
+            registry: operator_registry_info.key(),
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+// rewrites a registry created before multiple pausers were added, see LegacyPauseRegistry,
+// the legacy pauser stays a pauser
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigratePauseRegistry<'info> {
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [SEED_PREFIX, SEED_PAUSE],
+        bump
+    )]
+    /// CHECK: legacy pause registry, deserialized in handler
+    pub pause_registry: UncheckedAccount<'info>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    // approval is checked in handler, other authority signers are passed in remaining accounts
+    #[account(mut)]
+    pub operator_registry_authority: Signer<'info>, // pays rent of the grown registry
+    pub system_program: Program<'info, System>,
+}
+
+impl MigratePauseRegistry<'_> {
+    fn load_legacy_registry(pause_registry: &AccountInfo) -> Result<LegacyPauseRegistry> {
+        let data = pause_registry.try_borrow_data()?;
+        // legacy and current registries differ in size, a migrated registry can not be migrated again
+        if data.len() != LegacyPauseRegistry::size()
+            || !data.starts_with(&PauseRegistry::DISCRIMINATOR)
+        {
+            msg!("pause registry is not in legacy layout, size {}", data.len());
+            return err!(AgentError::RegistryMigrationInvalid);
+        }
+
+        LegacyPauseRegistry::deserialize(&mut &data[8..])
+            .map_err(|_| error!(AgentError::RegistryMigrationInvalid))
+    }
+
+    pub fn migrate_pause_registry(ctx: Context<MigratePauseRegistry>) -> Result<()> {
+        let authority = ctx.accounts.operator_registry_authority.key();
+        ctx.accounts
+            .operator_registry
+            .check_authority_approval(authority, ctx.remaining_accounts)?;
+
+        let pause_registry_info = ctx.accounts.pause_registry.to_account_info();
+        let legacy = Self::load_legacy_registry(&pause_registry_info)?;
+
+        let pause_registry = PauseRegistry {
+            bump: legacy.bump,
+            pausers: if legacy.pauser == Pubkey::default() {
+                vec![]
+            } else {
+                vec![legacy.pauser]
+            },
+            pending_pauser: Pubkey::default(),
+        };
+
+        // the current layout is larger, the authority pays the extra rent
+        let rent = Rent::get()?.minimum_balance(PauseRegistry::size());
+        let lamports = pause_registry_info.lamports();
+        if lamports < rent {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.operator_registry_authority.to_account_info(),
+                        to: pause_registry_info.clone(),
+                    },
+                ),
+                rent - lamports,
+            )?;
+        }
+        pause_registry_info.realloc(PauseRegistry::size(), true)?;
+
+        let mut data = pause_registry_info.try_borrow_mut_data()?;
+        pause_registry.try_serialize(&mut &mut data[..])?;
+
+        msg!("pause registry migrated, pauser {}", legacy.pauser);
+
+        emit_cpi!(RegistryMigratedEvent {
+            authority,
+            registry: pause_registry_info.key(),
+        });
+
+        Ok(())
+    }
+}
@@ -1,39 +1,63 @@
 pub use create_owner_account::*;
 pub use close_owner_account::*;
+pub use set_owner_swap_limits::*;
 pub use create_user_account::*;
 pub use withdraw::*;
+pub use exit_user_account::*;
 pub use create_user_token_account::*;
 pub use transfer_and_sync_wsol::*;
+pub use unwrap_wsol::*;
 pub use close_user_token_account::*;
+pub use owner_close_token_accounts::*;
 pub use swap_on_jupiter::*;
 pub use initialize::*;
 pub use manage::*;
 pub use collect_fee::*;
+pub use collect_platform_fee::*;
 pub use tip_jito::*;
 pub use manage_fee::*;
 pub use pause::*;
-pub use initialize_pause::*;
-pub use initialize_fee::*;
-pub use set_pauser::*;
+pub use accept_authority::*;
+pub use accept_pauser::*;
 pub use swap_on_pumpfun::*;
 pub use swap_on_pumpfun_v2::*;
+pub use swap_on_raydium_amm::*;
+pub use swap_on_pumpswap::*;
+pub use collect_fee_to_operator::*;
+pub use queue_proposal::*;
+pub use execute_proposal::*;
+pub use cancel_proposal::*;
+pub use migrate_operator_registry::*;
+pub use migrate_pause_registry::*;
 
 mod create_owner_account;
 mod close_owner_account;
+mod set_owner_swap_limits;
 mod create_user_account;
 mod withdraw;
+mod exit_user_account;
 mod create_user_token_account;
 mod close_user_token_account;
+mod owner_close_token_accounts;
 mod transfer_and_sync_wsol;
+mod unwrap_wsol;
 mod swap_on_jupiter;
 mod swap_on_pumpfun;
 mod swap_on_pumpfun_v2;
+mod swap_on_raydium_amm;
+mod swap_on_pumpswap;
 mod initialize;
 mod manage;
-mod initialize_fee;
 mod manage_fee;
 mod collect_fee;
+mod collect_platform_fee;
 mod tip_jito;
 mod pause;
-mod initialize_pause;
-mod set_pauser;
\ No newline at end of file
+mod accept_authority;
+mod accept_pauser;
+mod collect_fee_to_operator;
+mod queue_proposal;
+mod execute_proposal;
+mod cancel_proposal;
+mod migrate_operator_registry;
+mod migrate_pause_registry;
\ No newline at end of file
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, TokenAccount, TokenInterface};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct OwnerCloseTokenAccountsArgs {
+    pub user_account_nonce: u32,
+}
+
+// token accounts to close are passed in remaining accounts,
+// each of them must be empty or native WSOL, rent is returned to the user account
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(args: OwnerCloseTokenAccountsArgs)]
+pub struct OwnerCloseTokenAccounts<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_USER, owner_account.key().as_ref(), &args.user_account_nonce.to_le_bytes()],
+        bump
+    )]
+    pub user_account: SystemAccount<'info>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OWNER, owner.key().as_ref()],
+        bump
+    )]
+    pub owner_account: Account<'info, OwnerAccount>,
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> OwnerCloseTokenAccounts<'info> {
+    pub fn owner_close_token_accounts(
+        ctx: Context<'_, '_, 'info, 'info, OwnerCloseTokenAccounts<'info>>,
+        args: OwnerCloseTokenAccountsArgs,
+    ) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        require!(!ctx.remaining_accounts.is_empty(), AgentError::UserTokenAccountInvalid);
+
+        let owner_account_key = ctx.accounts.owner_account.key();
+        let user_account_key = ctx.accounts.user_account.key();
+        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
+        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEED_PREFIX,
+            SEED_USER,
+            owner_account_key.as_ref(),
+            user_account_nonce_bytes.as_ref(),
+            user_account_bump_bytes.as_ref(),
+        ]];
+
+        for token_account_info in ctx.remaining_accounts.iter() {
+            if token_account_info.owner != &ctx.accounts.token_program.key() {
+                msg!("token account {} not owned by token program", token_account_info.key());
+                return err!(AgentError::UserTokenAccountInvalid);
+            }
+
+            let token_account = InterfaceAccount::<TokenAccount>::try_from(token_account_info)?;
+            if token_account.owner != user_account_key {
+                msg!("token account {} not owned by user account", token_account_info.key());
+                return err!(AgentError::UserTokenAccountInvalid);
+            }
+            if !token_account.is_native() && token_account.amount != 0 {
+                msg!("token account {} has balance: {}", token_account_info.key(), token_account.amount);
+                return err!(AgentError::UserTokenAccountNotEmpty);
+            }
+
+            let lamports = token_account_info.lamports();
+
+            token_interface::close_account(CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: token_account_info.clone(),
+                    destination: ctx.accounts.user_account.to_account_info(),
+                    authority: ctx.accounts.user_account.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+
+            emit_cpi!(OwnerTokenAccountClosedEvent {
+                owner: ctx.accounts.owner.key(),
+                owner_account: owner_account_key,
+                user_account: user_account_key,
+                nonce: args.user_account_nonce,
+                token_account: token_account_info.key(),
+                mint: token_account.mint,
+                lamports,
+            });
+        }
+
+        Ok(())
+    }
+}
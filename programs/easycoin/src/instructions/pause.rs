@@ -2,7 +2,11 @@ use anchor_lang::prelude::*;
 
 use crate::state::*;
 use crate::errors::*;
+use crate::events::*;
 
+// registered pausers pause alone, the registry authority needs the approval of the authority signers,
+// checked in handler, other authority signers are passed in remaining accounts
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Pause<'info> {
     #[account(
@@ -22,15 +26,110 @@ pub struct Pause<'info> {
     pub operator_registry: Account<'info, OperatorRegistry>,
 }
 
+// only the registry authority can unpause, pausers can not
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Unpause<'info> {
+    // approval is checked in handler, other authority signers are passed in remaining accounts
+    pub operator_registry_authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+}
+
 impl Pause<'_> {
+    fn check_pauser_approval(&self, remaining_accounts: &[AccountInfo]) -> Result<()> {
+        if self.pause_registry.is_pauser(self.pauser.key()) {
+            return Ok(());
+        }
+        self.operator_registry
+            .check_authority_approval(self.pauser.key(), remaining_accounts)
+    }
+
+    pub fn pause(ctx: Context<Pause>, duration: i64) -> Result<()> {
+        ctx.accounts.check_pauser_approval(ctx.remaining_accounts)?;
 
-    pub fn pause(ctx: Context<Pause>) -> Result<()> {
-        ctx.accounts.operator_registry.pause()?;
+        ctx.accounts.operator_registry.pause(duration)?;
+        emit_cpi!(ProgramPausedEvent {
+            pauser: ctx.accounts.pauser.key(),
+            paused_until: ctx.accounts.operator_registry.paused_until,
+        });
         Ok(())
     }
 
-    pub fn unpause(ctx: Context<Pause>) -> Result<()> {
+    pub fn pause_features(ctx: Context<Pause>, features: u16, duration: i64) -> Result<()> {
+        ctx.accounts.check_pauser_approval(ctx.remaining_accounts)?;
+
+        ctx.accounts.operator_registry.pause_features(features, duration)?;
+        emit_cpi!(FeaturesPausedEvent {
+            pauser: ctx.accounts.pauser.key(),
+            features,
+            paused_features: ctx.accounts.operator_registry.paused_features,
+            paused_until: ctx.accounts.operator_registry.features_paused_until,
+        });
+        Ok(())
+    }
+}
+
+impl Unpause<'_> {
+
+    pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
+        ctx.accounts.operator_registry.check_authority_approval(
+            ctx.accounts.operator_registry_authority.key(),
+            ctx.remaining_accounts,
+        )?;
+
         ctx.accounts.operator_registry.unpause()?;
+        emit_cpi!(ProgramUnpausedEvent {
+            authority: ctx.accounts.operator_registry_authority.key(),
+        });
+        Ok(())
+    }
+
+    pub fn extend_pause(ctx: Context<Unpause>, duration: i64) -> Result<()> {
+        ctx.accounts.operator_registry.check_authority_approval(
+            ctx.accounts.operator_registry_authority.key(),
+            ctx.remaining_accounts,
+        )?;
+
+        ctx.accounts.operator_registry.extend_pause(duration)?;
+        emit_cpi!(PauseExtendedEvent {
+            authority: ctx.accounts.operator_registry_authority.key(),
+            paused_until: ctx.accounts.operator_registry.paused_until,
+        });
         Ok(())
     }
-}
\ No newline at end of file
+
+    pub fn extend_features_pause(ctx: Context<Unpause>, duration: i64) -> Result<()> {
+        ctx.accounts.operator_registry.check_authority_approval(
+            ctx.accounts.operator_registry_authority.key(),
+            ctx.remaining_accounts,
+        )?;
+
+        ctx.accounts.operator_registry.extend_features_pause(duration)?;
+        emit_cpi!(FeaturesPauseExtendedEvent {
+            authority: ctx.accounts.operator_registry_authority.key(),
+            paused_features: ctx.accounts.operator_registry.paused_features,
+            paused_until: ctx.accounts.operator_registry.features_paused_until,
+        });
+        Ok(())
+    }
+
+    pub fn unpause_features(ctx: Context<Unpause>, features: u16) -> Result<()> {
+        ctx.accounts.operator_registry.check_authority_approval(
+            ctx.accounts.operator_registry_authority.key(),
+            ctx.remaining_accounts,
+        )?;
+
+        ctx.accounts.operator_registry.unpause_features(features)?;
+        emit_cpi!(FeaturesUnpausedEvent {
+            authority: ctx.accounts.operator_registry_authority.key(),
+            features,
+            paused_features: ctx.accounts.operator_registry.paused_features,
+        });
+        Ok(())
+    }
+}
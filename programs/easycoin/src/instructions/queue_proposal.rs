@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct QueueProposalArgs {
+    pub action: ProposalAction,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(args: QueueProposalArgs)]
+pub struct QueueProposal<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    #[account(
+        init,
+        payer = operator_registry_authority,
+        space = Proposal::size(&args.action),
+        seeds = [SEED_PREFIX, SEED_PROPOSAL, &operator_registry.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    // approval is checked in handler, other authority signers are passed in remaining accounts
+    #[account(mut)]
+    pub operator_registry_authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl QueueProposal<'_> {
+    pub fn queue_proposal(ctx: Context<QueueProposal>, args: QueueProposalArgs) -> Result<()> {
+
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        ctx.accounts.operator_registry.check_authority_approval(
+            ctx.accounts.operator_registry_authority.key(),
+            ctx.remaining_accounts,
+        )?;
+
+        let operator_registry = &mut ctx.accounts.operator_registry;
+        let id = operator_registry.next_proposal_id()?;
+        let eta = Clock::get()?
+            .unix_timestamp
+            .checked_add(operator_registry.timelock_delay)
+            .ok_or(AgentError::TimelockDelayInvalid)?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.bump = ctx.bumps.proposal;
+        proposal.id = id;
+        proposal.proposer = ctx.accounts.operator_registry_authority.key();
+        proposal.eta = eta;
+        proposal.action = args.action.clone();
+
+        msg!("proposal {} queued, eta {}", id, eta);
+
+        emit_cpi!(ProposalQueuedEvent {
+            id,
+            proposer: ctx.accounts.operator_registry_authority.key(),
+            eta,
+            action: args.action,
+        });
+
+        Ok(())
+    }
+}
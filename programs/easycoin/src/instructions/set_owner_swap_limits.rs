@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetOwnerSwapLimits<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = OwnerSwapLimits::size(),
+        seeds = [SEED_PREFIX, SEED_SWAP_LIMITS, owner_account.key().as_ref()],
+        bump
+    )]
+    pub owner_swap_limits: Account<'info, OwnerSwapLimits>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OWNER, owner.key().as_ref()],
+        bump
+    )]
+    pub owner_account: Account<'info, OwnerAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>, // The wallet owner, must sign the transaction
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+}
+
+impl SetOwnerSwapLimits<'_> {
+    // a cap of 0 is no cap, removing both caps closes the account and returns the rent to the owner
+    pub fn set_owner_swap_limits(
+        ctx: Context<SetOwnerSwapLimits>,
+        max_slippage_bps: u16,
+        max_platform_fee_bps: u16,
+    ) -> Result<()> {
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+
+        let owner_swap_limits = &mut ctx.accounts.owner_swap_limits;
+        if max_slippage_bps == 0 && max_platform_fee_bps == 0 {
+            owner_swap_limits.close(ctx.accounts.owner.to_account_info())?;
+            msg!("owner swap limits removed");
+        } else {
+            owner_swap_limits.bump = ctx.bumps.owner_swap_limits;
+            owner_swap_limits.set_limits(max_slippage_bps, max_platform_fee_bps)?;
+        }
+
+        emit_cpi!(OwnerSwapLimitsSetEvent {
+            owner: ctx.accounts.owner.key(),
+            owner_account: ctx.accounts.owner_account.key(),
+            max_slippage_bps,
+            max_platform_fee_bps,
+        });
+
+        Ok(())
+    }
+}
@@ -1,25 +1,75 @@
 use anchor_lang::{
     prelude::*,
     solana_program::{instruction::Instruction, program::invoke_signed},
+    system_program,
+};
+use anchor_spl::{
+    token::{self, spl_token, Mint, SyncNative, Token},
+    token_interface::TokenAccount,
 };
-use anchor_spl::token_interface::TokenAccount;
 
 use crate::errors::*;
+use crate::events::*;
 use crate::external_program::Jupiter;
+use crate::instructions::{UnwrapWsol, WsolUnwrapAccounts};
 use crate::state::*;
 
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum JupiterRouteType {
     Route,
     SharedAccountRoute,
+    ExactOutRoute,
+    SharedAccountExactOutRoute,
+}
+
+// amounts declared in the route instruction data
+pub struct JupiterRouteAmounts {
+    pub exact_out: bool,
+    pub amount: u64,        // in_amount of exact in routes, out_amount of exact out routes
+    pub quoted_amount: u64, // quoted_out_amount of exact in routes, quoted_in_amount of exact out routes
+    pub slippage_bps: u16,
+    pub platform_fee_bps: u8,
+}
+
+impl JupiterRouteAmounts {
+    pub fn max_in_amount(&self) -> Result<u64> {
+        if !self.exact_out {
+            return Ok(self.amount);
+        }
+
+        // round up, unused input is unwrapped after the swap in auto wrap mode
+        let max_in_amount = u128::from(self.quoted_amount)
+            .checked_mul(FeeRegistry::MAX_BPS as u128 + u128::from(self.slippage_bps))
+            .ok_or(AgentError::SwapAmountOverflow)?
+            .div_ceil(FeeRegistry::MAX_BPS as u128);
+        u64::try_from(max_in_amount).map_err(|_| error!(AgentError::SwapAmountOverflow))
+    }
+
+    pub fn min_out_amount(&self) -> Result<u64> {
+        if self.exact_out {
+            return Ok(self.amount);
+        }
+
+        let slippage_bps = u128::from(self.slippage_bps).min(FeeRegistry::MAX_BPS as u128);
+        let min_out_amount = u128::from(self.quoted_amount)
+            .checked_mul(FeeRegistry::MAX_BPS as u128 - slippage_bps)
+            .ok_or(AgentError::SwapAmountOverflow)?
+            / FeeRegistry::MAX_BPS as u128;
+        // never above quoted_amount, so it fits in u64
+        Ok(min_out_amount as u64)
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub struct SwapOnJupiterArgs {
     user_account_nonce: u32,
     jupiter_data: Vec<u8>,
+    // wrap SOL of the user account before the swap and unwrap WSOL proceeds after it,
+    // requires the optional accounts
+    auto_wrap_wsol: bool,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: SwapOnJupiterArgs)]
 pub struct SwapOnJupiter<'info> {
@@ -32,9 +82,19 @@ pub struct SwapOnJupiter<'info> {
     #[account(mut)]
     pub owner_account: Account<'info, OwnerAccount>,
     #[account(
-        constraint = operator_registry.is_operator(operator.key()) @ AgentError::OperatorNotAllowed
+        seeds = [SEED_PREFIX, SEED_SWAP_LIMITS, owner_account.key().as_ref()],
+        bump
     )]
+    /// CHECK: owner swap limits, not created if the owner has no cap, loaded in handler
+    pub owner_swap_limits: UncheckedAccount<'info>,
     pub operator: Signer<'info>,
+    #[account(
+        mut, // operator swap volume is recorded
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_TRADER) @ AgentError::OperatorNotAllowed
+    )]
+    pub operator_account: Account<'info, OperatorAccount>,
     #[account(
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
@@ -46,29 +106,256 @@ pub struct SwapOnJupiter<'info> {
     )]
     pub fee_registry: Account<'info, FeeRegistry>,
     pub jupiter_program: Program<'info, Jupiter>,
+    // only for auto_wrap_wsol
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_UNWRAP, user_account.key().as_ref()],
+        bump
+    )]
+    /// CHECK: temporary native token account, created and closed in this instruction
+    pub temp_wsol_token_account: Option<UncheckedAccount<'info>>,
+    #[account(
+        address = spl_token::native_mint::id()
+    )]
+    pub native_mint: Option<Account<'info, Mint>>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Option<Program<'info, System>>,
 }
 
 impl<'info> SwapOnJupiter<'info> {
     pub fn route_type(data: &[u8]) -> Result<JupiterRouteType> {
         const ROUTE_DISCRIMINATOR: &[u8] = &[229, 23, 203, 151, 122, 227, 173, 42];
         const SHARED_ACCOUNT_ROUTE_DISCRIMINATOR: &[u8] = &[193, 32, 155, 51, 65, 214, 156, 129];
+        const EXACT_OUT_ROUTE_DISCRIMINATOR: &[u8] = &[208, 51, 239, 151, 123, 43, 237, 92];
+        const SHARED_ACCOUNT_EXACT_OUT_ROUTE_DISCRIMINATOR: &[u8] = &[176, 209, 105, 168, 154, 125, 69, 62];
 
         require!(data.len() >= 8, AgentError::NotSwapOnJupiter);
 
         match &data[..8] {
             ROUTE_DISCRIMINATOR => Ok(JupiterRouteType::Route),
             SHARED_ACCOUNT_ROUTE_DISCRIMINATOR => Ok(JupiterRouteType::SharedAccountRoute),
+            EXACT_OUT_ROUTE_DISCRIMINATOR => Ok(JupiterRouteType::ExactOutRoute),
+            SHARED_ACCOUNT_EXACT_OUT_ROUTE_DISCRIMINATOR => Ok(JupiterRouteType::SharedAccountExactOutRoute),
             _ => err!(AgentError::NotSwapOnJupiter),
         }
     }
 
+    // decodes the route with the instruction types generated from the vendored Jupiter IDL,
+    // route plans holding swap variants added to Jupiter after the IDL was vendored fail to decode,
+    // those routes fall back to the fixed trailing fields, see decode_route_trailing_fields
+    fn decode_route(data: &[u8], route_type: JupiterRouteType) -> Result<JupiterRouteAmounts> {
+        let amounts = match Self::decode_route_with_idl(data, route_type) {
+            Some(amounts) => amounts,
+            None => Self::decode_route_trailing_fields(data, route_type)?,
+        };
+
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "jupiter route: exact_out={} amount={} quoted_amount={} slippage_bps={} platform_fee_bps={}",
+            amounts.exact_out,
+            amounts.amount,
+            amounts.quoted_amount,
+            amounts.slippage_bps,
+            amounts.platform_fee_bps
+        );
+
+        Ok(amounts)
+    }
+
+    fn decode_route_with_idl(data: &[u8], route_type: JupiterRouteType) -> Option<JupiterRouteAmounts> {
+        // route_type is known, so data is at least 8 bytes long
+        let ix_data = &data[8..];
+
+        let amounts = match route_type {
+            JupiterRouteType::Route => {
+                let ix = jupiter_cpi::instruction::Route::try_from_slice(ix_data).ok()?;
+                JupiterRouteAmounts {
+                    exact_out: false,
+                    amount: ix._in_amount,
+                    quoted_amount: ix._quoted_out_amount,
+                    slippage_bps: ix._slippage_bps,
+                    platform_fee_bps: ix._platform_fee_bps,
+                }
+            }
+            JupiterRouteType::SharedAccountRoute => {
+                let ix = jupiter_cpi::instruction::SharedAccountsRoute::try_from_slice(ix_data).ok()?;
+                JupiterRouteAmounts {
+                    exact_out: false,
+                    amount: ix._in_amount,
+                    quoted_amount: ix._quoted_out_amount,
+                    slippage_bps: ix._slippage_bps,
+                    platform_fee_bps: ix._platform_fee_bps,
+                }
+            }
+            JupiterRouteType::ExactOutRoute => {
+                let ix = jupiter_cpi::instruction::ExactOutRoute::try_from_slice(ix_data).ok()?;
+                JupiterRouteAmounts {
+                    exact_out: true,
+                    amount: ix._out_amount,
+                    quoted_amount: ix._quoted_in_amount,
+                    slippage_bps: ix._slippage_bps,
+                    platform_fee_bps: ix._platform_fee_bps,
+                }
+            }
+            JupiterRouteType::SharedAccountExactOutRoute => {
+                let ix = jupiter_cpi::instruction::SharedAccountsExactOutRoute::try_from_slice(ix_data).ok()?;
+                JupiterRouteAmounts {
+                    exact_out: true,
+                    amount: ix._out_amount,
+                    quoted_amount: ix._quoted_in_amount,
+                    slippage_bps: ix._slippage_bps,
+                    platform_fee_bps: ix._platform_fee_bps,
+                }
+            }
+        };
+
+        Some(amounts)
+    }
+
+    // the instruction data ends with
+    // exact in: in_amount (u64), quoted_out_amount (u64), slippage_bps (u16), platform_fee_bps (u8)
+    // exact out: out_amount (u64), quoted_in_amount (u64), slippage_bps (u16), platform_fee_bps (u8)
+    fn decode_route_trailing_fields(data: &[u8], route_type: JupiterRouteType) -> Result<JupiterRouteAmounts> {
+        // shared routes start with the program authority id
+        let route_plan_start = match route_type {
+            JupiterRouteType::Route | JupiterRouteType::ExactOutRoute => 8,
+            JupiterRouteType::SharedAccountRoute | JupiterRouteType::SharedAccountExactOutRoute => 9,
+        };
+        let tail_start = data
+            .len()
+            .checked_sub(8 + 8 + 2 + 1)
+            .filter(|tail_start| *tail_start >= route_plan_start)
+            .ok_or(AgentError::JupiterRouteDecodeFailed)?;
+        let tail = &data[tail_start..];
+
+        Ok(JupiterRouteAmounts {
+            exact_out: matches!(
+                route_type,
+                JupiterRouteType::ExactOutRoute | JupiterRouteType::SharedAccountExactOutRoute
+            ),
+            amount: u64::from_le_bytes(tail[0..8].try_into().unwrap()),
+            quoted_amount: u64::from_le_bytes(tail[8..16].try_into().unwrap()),
+            slippage_bps: u16::from_le_bytes(tail[16..18].try_into().unwrap()),
+            platform_fee_bps: tail[18],
+        })
+    }
+
+    // declared slippage and platform fee must be within protocol and owner limits
+    fn check_route_limits(
+        amounts: &JupiterRouteAmounts,
+        fee_registry: &FeeRegistry,
+        owner_swap_limits: &UncheckedAccount,
+    ) -> Result<()> {
+        let owner_limits = OwnerSwapLimits::load(owner_swap_limits)?;
+
+        let slippage_bps = u64::from(amounts.slippage_bps);
+        let max_slippage_bps = fee_registry.get_fee_value(FeeIndex::MaxSlippageBps)?;
+        let owner_max_slippage_bps = owner_limits.max_slippage_bps;
+        if slippage_bps > max_slippage_bps
+            || (owner_max_slippage_bps != 0 && amounts.slippage_bps > owner_max_slippage_bps)
+        {
+            msg!(
+                "slippage {} bps exceeds limit, protocol {} bps, owner {} bps",
+                slippage_bps,
+                max_slippage_bps,
+                owner_max_slippage_bps
+            );
+            return err!(AgentError::JupiterSlippageExceeded);
+        }
+
+        let platform_fee_bps = u64::from(amounts.platform_fee_bps);
+        let max_platform_fee_bps = fee_registry.get_fee_value(FeeIndex::MaxPlatformFeeBps)?;
+        let owner_max_platform_fee_bps = owner_limits.max_platform_fee_bps;
+        if platform_fee_bps > max_platform_fee_bps
+            || (owner_max_platform_fee_bps != 0 && platform_fee_bps > u64::from(owner_max_platform_fee_bps))
+        {
+            msg!(
+                "platform fee {} bps exceeds limit, protocol {} bps, owner {} bps",
+                platform_fee_bps,
+                max_platform_fee_bps,
+                owner_max_platform_fee_bps
+            );
+            return err!(AgentError::JupiterPlatformFeeExceeded);
+        }
+
+        Ok(())
+    }
+
+    // a platform fee below the swap fee is topped up with the uncovered share of swap_fee as trade fee
+    fn platform_fee_shortfall(swap_fee: u64, platform_fee: u64, expected_platform_fee: u64) -> Result<u64> {
+        if platform_fee >= expected_platform_fee {
+            return Ok(0);
+        }
+
+        #[cfg(feature = "enable-log")]
+        msg!("platform fee {} below expected {}", platform_fee, expected_platform_fee);
+
+        // never above swap_fee, so it fits in u64
+        let shortfall = u128::from(swap_fee)
+            .checked_mul(u128::from(expected_platform_fee - platform_fee))
+            .ok_or(AgentError::SwapFeeOverflow)?
+            / u128::from(expected_platform_fee);
+        Ok(shortfall as u64)
+    }
+
+    // index of user_transfer_authority in remaining accounts, it is signed by user account
+    fn user_transfer_authority_index(route_type: JupiterRouteType) -> usize {
+        match route_type {
+            JupiterRouteType::Route | JupiterRouteType::ExactOutRoute => 1,
+            JupiterRouteType::SharedAccountRoute | JupiterRouteType::SharedAccountExactOutRoute => 2,
+        }
+    }
+
+    // index of platform_fee_account in remaining accounts
+    fn platform_fee_account_index(route_type: JupiterRouteType) -> usize {
+        match route_type {
+            JupiterRouteType::Route => 6,
+            JupiterRouteType::ExactOutRoute => 7,
+            JupiterRouteType::SharedAccountRoute | JupiterRouteType::SharedAccountExactOutRoute => 9,
+        }
+    }
+
+    // a route with platform fee must pay it to a token account of the fee registry,
+    // in either the input or the output token
+    fn validate_platform_fee_account(
+        fee_registry: &Account<FeeRegistry>,
+        route_type: JupiterRouteType,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        source_mint: Pubkey,
+        destination_mint: Pubkey,
+    ) -> Result<InterfaceAccount<'info, TokenAccount>> {
+        let platform_fee_account_info = remaining_accounts
+            .get(Self::platform_fee_account_index(route_type))
+            .ok_or(AgentError::NotJupiterRoute)?;
+        let platform_fee_account: InterfaceAccount<'info, TokenAccount> =
+            InterfaceAccount::try_from(platform_fee_account_info)
+                .map_err(|_| AgentError::PlatformFeeAccountInvalid)?;
+        require!(
+            platform_fee_account.owner == fee_registry.key(),
+            AgentError::PlatformFeeAccountInvalid
+        );
+        require!(
+            platform_fee_account.mint == source_mint || platform_fee_account.mint == destination_mint,
+            AgentError::PlatformFeeAccountInvalid
+        );
+
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "platform_fee_account: {}",
+            platform_fee_account_info.key().to_string()
+        );
+
+        Ok(platform_fee_account)
+    }
+
     fn validate_destination_token_account(
         user_account: SystemAccount,
         route_type: JupiterRouteType,
         remaining_accounts: &'info [AccountInfo<'info>],
     ) -> Result<InterfaceAccount<'info, TokenAccount>> {
         match route_type {
-            JupiterRouteType::Route => {
+            // exact out route shares the leading accounts of route
+            JupiterRouteType::Route | JupiterRouteType::ExactOutRoute => {
                 let user_destination_token_account_info = remaining_accounts
                     .get(3)
                     .ok_or(AgentError::NotJupiterRoute)?;
@@ -105,8 +392,32 @@ impl<'info> SwapOnJupiter<'info> {
                 );
                 Ok(user_destination_token_account)
             }
-            JupiterRouteType::SharedAccountRoute => {
-                return err!(AgentError::NotJupiterRoute);
+            JupiterRouteType::SharedAccountRoute | JupiterRouteType::SharedAccountExactOutRoute => {
+                // token_program, program_authority, user_transfer_authority, source_token_account,
+                // program_source_token_account, program_destination_token_account,
+                // destination_token_account, source_mint, destination_mint, ...
+                let user_destination_token_account_info = remaining_accounts
+                    .get(6)
+                    .ok_or(AgentError::NotJupiterRoute)?;
+                let user_destination_token_account: InterfaceAccount<'info, TokenAccount> =
+                    InterfaceAccount::try_from(user_destination_token_account_info)?;
+                require!(
+                    user_destination_token_account.owner == user_account.key(),
+                    AgentError::JupiterRouteDestinationInvalid
+                );
+
+                let destination_mint_info = remaining_accounts
+                    .get(8)
+                    .ok_or(AgentError::NotJupiterRoute)?;
+                require!(
+                    destination_mint_info.key() == user_destination_token_account.mint,
+                    AgentError::JupiterRouteDestinationInvalid
+                );
+
+                #[cfg(feature = "enable-log")]
+                msg!("destination token account authority is valid");
+
+                Ok(user_destination_token_account)
             }
         }
     }
@@ -114,14 +425,16 @@ impl<'info> SwapOnJupiter<'info> {
     fn validate_source_token_account(
         user_account: SystemAccount,
         route_type: JupiterRouteType,
+        data: &[u8],
         remaining_accounts: &'info [AccountInfo<'info>],
     ) -> Result<InterfaceAccount<'info, TokenAccount>> {
         // verify user_account matches the info in remaining accounts
         match route_type {
-            JupiterRouteType::Route => {
+            // exact out route shares the leading accounts of route
+            JupiterRouteType::Route | JupiterRouteType::ExactOutRoute => {
                 // verify user transfer authority matches
                 let user_transfer_authority_account_info = remaining_accounts
-                    .get(1)
+                    .get(Self::user_transfer_authority_index(route_type))
                     .ok_or(AgentError::NotJupiterRoute)?;
                 require!(
                     user_transfer_authority_account_info.key() == user_account.key(),
@@ -141,36 +454,85 @@ impl<'info> SwapOnJupiter<'info> {
 
                 Ok(user_source_token_account)
             }
-            JupiterRouteType::SharedAccountRoute => {
-                return err!(AgentError::NotJupiterRoute);
+            JupiterRouteType::SharedAccountRoute | JupiterRouteType::SharedAccountExactOutRoute => {
+                // verify program authority matches the id in instruction data
+                let id = *data.get(8).ok_or(AgentError::NotJupiterRoute)?;
+                let program_authority_account_info = remaining_accounts
+                    .get(1)
+                    .ok_or(AgentError::NotJupiterRoute)?;
+                require!(
+                    program_authority_account_info.key() == Jupiter::program_authority(id),
+                    AgentError::JupiterRouteSourceInvalid
+                );
+
+                // verify user transfer authority matches
+                let user_transfer_authority_account_info = remaining_accounts
+                    .get(Self::user_transfer_authority_index(route_type))
+                    .ok_or(AgentError::NotJupiterRoute)?;
+                require!(
+                    user_transfer_authority_account_info.key() == user_account.key(),
+                    AgentError::JupiterRouteSourceInvalid
+                );
+
+                // verify user source token account matches
+                let user_source_token_account_info = remaining_accounts
+                    .get(3)
+                    .ok_or(AgentError::NotJupiterRoute)?;
+                let user_source_token_account: InterfaceAccount<'info, TokenAccount> =
+                    InterfaceAccount::try_from(user_source_token_account_info)?;
+                require!(
+                    user_source_token_account.owner == user_account.key(),
+                    AgentError::JupiterRouteSourceInvalid
+                );
+
+                let source_mint_info = remaining_accounts
+                    .get(7)
+                    .ok_or(AgentError::NotJupiterRoute)?;
+                require!(
+                    source_mint_info.key() == user_source_token_account.mint,
+                    AgentError::JupiterRouteSourceInvalid
+                );
+
+                Ok(user_source_token_account)
             }
         }
     }
 
     pub fn swap_on_jupiter(
-        ctx: Context<'_, '_, 'info, 'info, SwapOnJupiter>,
+        ctx: Context<'_, '_, 'info, 'info, SwapOnJupiter<'info>>,
         args: SwapOnJupiterArgs,
     ) -> Result<()> {
         require!(
-            ctx.accounts.operator_registry.program_ok(),
+            ctx.accounts.operator_registry.exit_ok(),
             AgentError::ProgramStatusError
         );
+        require!(
+            ctx.accounts.operator_registry.feature_ok(FEATURE_JUPITER_SWAP),
+            AgentError::FeaturePaused
+        );
 
         let remaining_accounts = ctx.remaining_accounts;
         let data = args.jupiter_data;
         let route_type = Self::route_type(&data)?;
+        let route_amounts = Self::decode_route(&data, route_type)?;
+        Self::check_route_limits(
+            &route_amounts,
+            &ctx.accounts.fee_registry,
+            &ctx.accounts.owner_swap_limits,
+        )?;
         let user_account = &ctx.accounts.user_account;
 
-        let user_source_token_account = Self::validate_source_token_account(
+        let mut user_source_token_account = Self::validate_source_token_account(
             user_account.clone(),
             route_type,
+            &data,
             remaining_accounts,
         )?;
 
         #[cfg(feature = "enable-log")]
         msg!("source token account is valid");
 
-        let user_destination_token_account = Self::validate_destination_token_account(
+        let mut user_destination_token_account = Self::validate_destination_token_account(
             user_account.clone(),
             route_type,
             remaining_accounts,
@@ -179,19 +541,118 @@ impl<'info> SwapOnJupiter<'info> {
         #[cfg(feature = "enable-log")]
         msg!("destination token account is valid");
 
+        // trade fee is taken by Jupiter atomically, only a shortfall of it is recorded as due fee
+        let mut platform_fee_account = if route_amounts.platform_fee_bps > 0 {
+            require!(
+                ctx.accounts
+                    .fee_registry
+                    .platform_fee_covers_swap_fee(u64::from(route_amounts.platform_fee_bps))?,
+                AgentError::PlatformFeeTooLow
+            );
+            Some(Self::validate_platform_fee_account(
+                &ctx.accounts.fee_registry,
+                route_type,
+                remaining_accounts,
+                user_source_token_account.mint,
+                user_destination_token_account.mint,
+            )?)
+        } else {
+            None
+        };
+        let platform_fee_balance_before = platform_fee_account
+            .as_ref()
+            .map_or(0, |account| account.amount);
+
         #[cfg(feature = "enable-log")]
         msg!("account validation passed!");
 
-        let mut wsol_token_account = match (
+        let source_is_wsol = match (
             user_source_token_account.is_native(),
             user_destination_token_account.is_native(),
         ) {
-            (true, false) => user_source_token_account,
-            (false, true) => user_destination_token_account,
+            (true, false) => true,
+            (false, true) => false,
             _ => return err!(AgentError::NoWsolTokenAccount),
         };
 
-        let wsol_balance_before = wsol_token_account.amount;
+        let mut source_balance_before = user_source_token_account.amount;
+        let destination_balance_before = user_destination_token_account.amount;
+        let wsol_balance_before = if source_is_wsol {
+            source_balance_before
+        } else {
+            destination_balance_before
+        };
+        let sol_balance_before = user_account.lamports();
+
+        let owner_account_key = ctx.accounts.owner_account.key();
+        let user_account_key = user_account.key();
+        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
+        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
+        let user_account_seeds: &[&[u8]] = &[
+            SEED_PREFIX,
+            SEED_USER,
+            owner_account_key.as_ref(),
+            user_account_nonce_bytes.as_ref(),
+            user_account_bump_bytes.as_ref(),
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[user_account_seeds];
+
+        let wsol_unwrap_accounts = if args.auto_wrap_wsol {
+            let wsol_token_account = if source_is_wsol {
+                &user_source_token_account
+            } else {
+                &user_destination_token_account
+            };
+            match (
+                &ctx.accounts.temp_wsol_token_account,
+                &ctx.accounts.native_mint,
+                &ctx.accounts.token_program,
+                &ctx.accounts.system_program,
+            ) {
+                (Some(temp_wsol_token_account), Some(native_mint), Some(token_program), Some(system_program)) => {
+                    Some(WsolUnwrapAccounts {
+                        wsol_token_account: wsol_token_account.to_account_info(),
+                        temp_wsol_token_account: temp_wsol_token_account.to_account_info(),
+                        native_mint: native_mint.to_account_info(),
+                        user_account: user_account.to_account_info(),
+                        token_program: token_program.to_account_info(),
+                        system_program: system_program.to_account_info(),
+                    })
+                }
+                _ => return err!(AgentError::AutoWrapAccountsMissing),
+            }
+        } else {
+            None
+        };
+
+        // wrap in_amount of SOL before buying
+        if let (Some(wsol_accounts), true) = (&wsol_unwrap_accounts, source_is_wsol) {
+            let in_amount = route_amounts.max_in_amount()?;
+
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    wsol_accounts.system_program.clone(),
+                    system_program::Transfer {
+                        from: wsol_accounts.user_account.clone(),
+                        to: wsol_accounts.wsol_token_account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                in_amount,
+            )?;
+            token::sync_native(CpiContext::new(
+                wsol_accounts.token_program.clone(),
+                SyncNative {
+                    account: wsol_accounts.wsol_token_account.clone(),
+                },
+            ))?;
+
+            #[cfg(feature = "enable-log")]
+            msg!("auto wrap: {}", in_amount);
+
+            user_source_token_account.reload()?;
+            source_balance_before = user_source_token_account.amount;
+        }
 
         let mut accounts: Vec<AccountMeta> = remaining_accounts
             .iter()
@@ -202,9 +663,11 @@ impl<'info> SwapOnJupiter<'info> {
             })
             .collect();
 
-        // specify user account (2nd account in remaining_accounts) as signer
-        if let Some(second_account) = accounts.get_mut(1) {
-            second_account.is_signer = true;
+        // specify user account (user_transfer_authority in remaining_accounts) as signer
+        if let Some(user_transfer_authority) =
+            accounts.get_mut(Self::user_transfer_authority_index(route_type))
+        {
+            user_transfer_authority.is_signer = true;
         }
 
         let accounts_infos: Vec<AccountInfo> = remaining_accounts
@@ -212,17 +675,6 @@ impl<'info> SwapOnJupiter<'info> {
             .map(|acc| AccountInfo { ..acc.clone() })
             .collect();
 
-        let owner_account_key = ctx.accounts.owner_account.key();
-        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
-        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            SEED_PREFIX,
-            SEED_USER,
-            owner_account_key.as_ref(),
-            user_account_nonce_bytes.as_ref(),
-            user_account_bump_bytes.as_ref(),
-        ]];
-
         invoke_signed(
             &Instruction {
                 program_id: ctx.accounts.jupiter_program.key(),
@@ -233,14 +685,102 @@ impl<'info> SwapOnJupiter<'info> {
             signer_seeds,
         )?;
 
-        wsol_token_account.reload()?;
-        let wsol_balance_after = wsol_token_account.amount;
-        let trade_fee = ctx
+        user_source_token_account.reload()?;
+        user_destination_token_account.reload()?;
+
+        // enforce declared amounts of the route
+        let amount_in = source_balance_before.saturating_sub(user_source_token_account.amount);
+        let amount_out = user_destination_token_account
+            .amount
+            .saturating_sub(destination_balance_before);
+        if amount_in > route_amounts.max_in_amount()? || amount_out < route_amounts.min_out_amount()? {
+            msg!("jupiter route amounts invalid: in {} out {}", amount_in, amount_out);
+            return err!(AgentError::JupiterRouteAmountInvalid);
+        }
+
+        let wsol_balance_after = if source_is_wsol {
+            user_source_token_account.amount
+        } else {
+            user_destination_token_account.amount
+        };
+
+        // in auto wrap mode, unwrap what the swap added to the WSOL token account,
+        // and compute fee on the lamport delta of the user account
+        let (balance_before, balance_after) = if let Some(wsol_accounts) = &wsol_unwrap_accounts {
+            let unwrap_amount = wsol_balance_after.saturating_sub(wsol_balance_before);
+            if unwrap_amount > 0 {
+                // the temporary WSOL token account is created at its PDA, so it signs as well
+                let temp_wsol_bump_bytes = ctx
+                    .bumps
+                    .temp_wsol_token_account
+                    .ok_or(AgentError::AutoWrapAccountsMissing)?
+                    .to_le_bytes();
+                let unwrap_signer_seeds: &[&[&[u8]]] = &[
+                    user_account_seeds,
+                    &[
+                        SEED_PREFIX,
+                        SEED_UNWRAP,
+                        user_account_key.as_ref(),
+                        temp_wsol_bump_bytes.as_ref(),
+                    ],
+                ];
+                UnwrapWsol::unwrap_to_user_account(wsol_accounts, unwrap_signer_seeds, unwrap_amount)?;
+
+                #[cfg(feature = "enable-log")]
+                msg!("auto unwrap: {}", unwrap_amount);
+            }
+            (sol_balance_before, user_account.lamports())
+        } else {
+            (wsol_balance_before, wsol_balance_after)
+        };
+
+        let swap_fee = ctx
             .accounts
             .fee_registry
-            .calculate_swap_fee(wsol_balance_before, wsol_balance_after)?;
+            .calculate_swap_fee(balance_before, balance_after)?;
+        let trade_fee = if let Some(platform_fee_account) = &mut platform_fee_account {
+            platform_fee_account.reload()?;
+            let platform_fee = platform_fee_account
+                .amount
+                .saturating_sub(platform_fee_balance_before);
+
+            emit_cpi!(PlatformFeeTakenEvent {
+                owner_account: owner_account_key,
+                user_account: user_account_key,
+                nonce: args.user_account_nonce,
+                operator: ctx.accounts.operator.key(),
+                platform_fee_account: platform_fee_account.key(),
+                mint: platform_fee_account.mint,
+                platform_fee_bps: route_amounts.platform_fee_bps,
+                amount: platform_fee,
+            });
+
+            // swap fee expressed in the fee mint, on the traded amount before the platform fee
+            let fee_mint_amount = if platform_fee_account.mint == user_source_token_account.mint {
+                amount_in
+            } else {
+                amount_out.saturating_add(platform_fee)
+            };
+            let expected_platform_fee = ctx
+                .accounts
+                .fee_registry
+                .calculate_swap_fee(0, fee_mint_amount)?;
+            Self::platform_fee_shortfall(swap_fee, platform_fee, expected_platform_fee)?
+        } else {
+            swap_fee
+        };
 
-        // record swap fee
+        // only sells into SOL are allowed in wind down
+        if ctx.accounts.operator_registry.is_wind_down() {
+            require!(balance_after > balance_before, AgentError::WindDownSellOnly);
+        }
+
+        // check operator volume quota
+        ctx.accounts
+            .operator_account
+            .record_volume(balance_after.abs_diff(balance_before))?;
+
+        // record swap fee, zero if it is fully taken as platform fee
         ctx.accounts
             .owner_account
             .add_user_account_due_fee(args.user_account_nonce, trade_fee)?;
@@ -254,6 +794,19 @@ impl<'info> SwapOnJupiter<'info> {
             AgentError::UserAccountBalanceNotEnough
         );
 
+        emit_cpi!(SwapEvent {
+            owner_account: owner_account_key,
+            user_account: user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            venue_program: ctx.accounts.jupiter_program.key(),
+            input_mint: user_source_token_account.mint,
+            output_mint: user_destination_token_account.mint,
+            amount_in,
+            amount_out,
+            trade_fee,
+        });
+
         Ok(())
     }
 }
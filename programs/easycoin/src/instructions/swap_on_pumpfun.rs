@@ -2,9 +2,10 @@ use anchor_lang::{
     prelude::*,
     solana_program::{instruction::Instruction, program::invoke_signed},
 };
-use anchor_spl::token_interface::TokenAccount;
+use anchor_spl::{token::spl_token, token_interface::TokenAccount};
 
 use crate::errors::*;
+use crate::events::*;
 use crate::state::*;
 use crate::external_program::Pumpfun;
 
@@ -16,6 +17,7 @@ pub struct SwapOnPumpfunArgs {
 }
 
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: SwapOnPumpfunArgs)]
 pub struct SwapOnPumpfun<'info> {
@@ -27,10 +29,14 @@ pub struct SwapOnPumpfun<'info> {
     pub user_account: SystemAccount<'info>,
     #[account(mut)]
     pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
     #[account(
-        constraint = operator_registry.is_operator(operator.key()) @ AgentError::OperatorNotAllowed
+        mut, // operator swap volume is recorded
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_TRADER) @ AgentError::OperatorNotAllowed
     )]
-    pub operator: Signer<'info>,
+    pub operator_account: Account<'info, OperatorAccount>,
     #[account(
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
@@ -48,7 +54,7 @@ impl<'info> SwapOnPumpfun<'info> {
     fn validate_user_token_account(
         user_account: SystemAccount,
         remaining_accounts: &'info [AccountInfo<'info>],
-    ) -> Result<()> {
+    ) -> Result<InterfaceAccount<'info, TokenAccount>> {
         // validate user account
         let user_account_info = remaining_accounts
             .get(6)
@@ -69,7 +75,33 @@ impl<'info> SwapOnPumpfun<'info> {
             AgentError::PumpfunUserTokenAccountInvalid
         );
 
-        Ok(())
+        Ok(user_token_account)
+    }
+
+    // shared with swap_on_pumpfun_v2,
+    // buy spends SOL for token, sell spends token for SOL
+    pub(crate) fn swap_event_amounts(
+        mint: Pubkey,
+        lamports_before: u64,
+        lamports_after: u64,
+        token_balance_before: u64,
+        token_balance_after: u64,
+    ) -> (Pubkey, Pubkey, u64, u64) {
+        if lamports_after < lamports_before {
+            (
+                spl_token::native_mint::id(),
+                mint,
+                lamports_before - lamports_after,
+                token_balance_after.saturating_sub(token_balance_before),
+            )
+        } else {
+            (
+                mint,
+                spl_token::native_mint::id(),
+                token_balance_before.saturating_sub(token_balance_after),
+                lamports_after - lamports_before,
+            )
+        }
     }
 
     pub fn swap_on_pumpfun(
@@ -77,15 +109,21 @@ impl<'info> SwapOnPumpfun<'info> {
         args: SwapOnPumpfunArgs,
     ) -> Result<()> {
         require!(
-            ctx.accounts.operator_registry.program_ok(),
+            ctx.accounts.operator_registry.exit_ok(),
             AgentError::ProgramStatusError
         );
+        require!(
+            ctx.accounts.operator_registry.feature_ok(FEATURE_PUMPFUN_SWAP),
+            AgentError::FeaturePaused
+        );
 
         let remaining_accounts = ctx.remaining_accounts;
         let data = args.pumpfun_data;
         let user_account = &ctx.accounts.user_account;
 
-        Self::validate_user_token_account(user_account.clone(), remaining_accounts)?;
+        let mut user_token_account =
+            Self::validate_user_token_account(user_account.clone(), remaining_accounts)?;
+        let token_balance_before = user_token_account.amount;
 
         #[cfg(feature = "enable-log")]
         msg!("user token account is valid");
@@ -143,6 +181,16 @@ impl<'info> SwapOnPumpfun<'info> {
             .fee_registry
             .calculate_swap_fee(user_account_lamports_before, user_account_lamports_after)?;
 
+        // only sells into SOL are allowed in wind down
+        if ctx.accounts.operator_registry.is_wind_down() {
+            require!(user_account_lamports_after > user_account_lamports_before, AgentError::WindDownSellOnly);
+        }
+
+        // check operator volume quota
+        ctx.accounts
+            .operator_account
+            .record_volume(user_account_lamports_after.abs_diff(user_account_lamports_before))?;
+
         // record swap fee
         ctx.accounts
         .owner_account
@@ -157,6 +205,28 @@ impl<'info> SwapOnPumpfun<'info> {
             AgentError::UserAccountBalanceNotEnough
         );
 
+        user_token_account.reload()?;
+        let (input_mint, output_mint, amount_in, amount_out) = Self::swap_event_amounts(
+            user_token_account.mint,
+            user_account_lamports_before,
+            user_account_lamports_after,
+            token_balance_before,
+            user_token_account.amount,
+        );
+
+        emit_cpi!(SwapEvent {
+            owner_account: owner_account_key,
+            user_account: user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            venue_program: ctx.accounts.pumpfun_program.key(),
+            input_mint,
+            output_mint,
+            amount_in,
+            amount_out,
+            trade_fee,
+        });
+
         Ok(())
     }
 
@@ -7,7 +7,9 @@ use anchor_lang::{
 use anchor_spl::token_interface::TokenAccount;
 
 use crate::errors::*;
+use crate::events::*;
 use crate::external_program::Pumpfun;
+use crate::instructions::SwapOnPumpfun;
 use crate::state::*;
 use pumpfun_cpi;
 
@@ -40,6 +42,7 @@ pub struct SwapOnPumpfunV2Args {
     op: SwapOnPumpfunV2Op,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: SwapOnPumpfunV2Args)]
 pub struct SwapOnPumpfunV2<'info> {
@@ -51,10 +54,14 @@ pub struct SwapOnPumpfunV2<'info> {
     pub user_account: SystemAccount<'info>,
     #[account(mut)]
     pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
     #[account(
-        constraint = operator_registry.is_operator(operator.key()) @ AgentError::OperatorNotAllowed
+        mut, // operator swap volume is recorded
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_TRADER) @ AgentError::OperatorNotAllowed
     )]
-    pub operator: Signer<'info>,
+    pub operator_account: Account<'info, OperatorAccount>,
     #[account(
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
@@ -72,7 +79,7 @@ impl<'info> SwapOnPumpfunV2<'info> {
     fn validate_user_token_account(
         user_account: SystemAccount,
         remaining_accounts: &'info [AccountInfo<'info>],
-    ) -> Result<()> {
+    ) -> Result<InterfaceAccount<'info, TokenAccount>> {
         // validate user account
         let user_account_info = remaining_accounts
             .get(6)
@@ -93,7 +100,7 @@ impl<'info> SwapOnPumpfunV2<'info> {
             AgentError::PumpfunUserTokenAccountInvalid
         );
 
-        Ok(())
+        Ok(user_token_account)
     }
 
     pub fn swap_on_pumpfun_v2(
@@ -101,14 +108,20 @@ impl<'info> SwapOnPumpfunV2<'info> {
         args: SwapOnPumpfunV2Args,
     ) -> Result<()> {
         require!(
-            ctx.accounts.operator_registry.program_ok(),
+            ctx.accounts.operator_registry.exit_ok(),
             AgentError::ProgramStatusError
         );
+        require!(
+            ctx.accounts.operator_registry.feature_ok(FEATURE_PUMPFUN_SWAP),
+            AgentError::FeaturePaused
+        );
 
         let remaining_accounts = ctx.remaining_accounts;
         let user_account = &ctx.accounts.user_account;
 
-        Self::validate_user_token_account(user_account.clone(), remaining_accounts)?;
+        let mut user_token_account =
+            Self::validate_user_token_account(user_account.clone(), remaining_accounts)?;
+        let token_balance_before = user_token_account.amount;
 
         #[cfg(feature = "enable-log")]
         msg!("user token account is valid");
@@ -127,6 +140,15 @@ impl<'info> SwapOnPumpfunV2<'info> {
             bonding_curve
         );
 
+        // graduated tokens trade on PumpSwap, the bonding curve would reject the trade
+        if bonding_curve.complete {
+            msg!(
+                "bonding curve {} is complete, use swap_on_pumpswap",
+                bonding_curve_account_info.key()
+            );
+            return err!(AgentError::PumpfunBondingCurveComplete);
+        }
+
         // get user account lamports before
         let user_account_lamports_before = user_account.lamports();
         #[cfg(feature = "enable-log")]
@@ -260,6 +282,16 @@ impl<'info> SwapOnPumpfunV2<'info> {
             .fee_registry
             .calculate_swap_fee(user_account_lamports_before, user_account_lamports_after)?;
 
+        // only sells into SOL are allowed in wind down
+        if ctx.accounts.operator_registry.is_wind_down() {
+            require!(user_account_lamports_after > user_account_lamports_before, AgentError::WindDownSellOnly);
+        }
+
+        // check operator volume quota
+        ctx.accounts
+            .operator_account
+            .record_volume(user_account_lamports_after.abs_diff(user_account_lamports_before))?;
+
         // record swap fee
         ctx.accounts
             .owner_account
@@ -277,6 +309,28 @@ impl<'info> SwapOnPumpfunV2<'info> {
             AgentError::UserAccountBalanceNotEnough
         );
 
+        user_token_account.reload()?;
+        let (input_mint, output_mint, amount_in, amount_out) = SwapOnPumpfun::swap_event_amounts(
+            user_token_account.mint,
+            user_account_lamports_before,
+            user_account_lamports_after,
+            token_balance_before,
+            user_token_account.amount,
+        );
+
+        emit_cpi!(SwapEvent {
+            owner_account: owner_account_key,
+            user_account: user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            venue_program: ctx.accounts.pumpfun_program.key(),
+            input_mint,
+            output_mint,
+            amount_in,
+            amount_out,
+            trade_fee,
+        });
+
         Ok(())
     }
 }
@@ -0,0 +1,352 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke_signed},
+};
+use anchor_spl::{token::spl_token, token_interface::TokenAccount};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::external_program::Pumpswap;
+use crate::state::*;
+
+// leading fields of the PumpSwap pool account, later fields are not needed,
+// it is owned by PumpSwap, so it is read by hand instead of as an account of this program
+#[derive(AnchorDeserialize, Debug)]
+#[allow(dead_code)] // unused fields are deserialized to reach the token accounts
+struct Pool {
+    pub pool_bump: u8,
+    pub index: u16,
+    pub creator: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    pub lp_supply: u64,
+}
+
+impl Pool {
+    // anchor account discriminator of PumpSwap, sha256("account:Pool")[..8]
+    const DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum SwapOnPumpswapOp {
+    Buy {
+        base_amount_out: u64,
+        max_quote_amount_in: u64,
+    },
+    Sell {
+        base_amount_in: u64,
+        min_quote_amount_out: u64,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct SwapOnPumpswapArgs {
+    user_account_nonce: u32,
+    op: SwapOnPumpswapOp,
+}
+
+// PumpSwap buy or sell accounts are passed in remaining accounts:
+// pool, user, global_config, base_mint, quote_mint, user_base_token_account,
+// user_quote_token_account, pool_base_token_account, pool_quote_token_account, ...
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(args: SwapOnPumpswapArgs)]
+pub struct SwapOnPumpswap<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_USER, owner_account.key().as_ref(), &args.user_account_nonce.to_le_bytes()],
+        bump
+    )]
+    pub user_account: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
+    #[account(
+        mut, // operator swap volume is recorded
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_TRADER) @ AgentError::OperatorNotAllowed
+    )]
+    pub operator_account: Account<'info, OperatorAccount>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_FEE],
+        bump
+    )]
+    pub fee_registry: Account<'info, FeeRegistry>,
+    pub pumpswap_program: Program<'info, Pumpswap>,
+}
+
+impl<'info> SwapOnPumpswap<'info> {
+    const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+    const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+    // index of user in remaining accounts, it is signed by user account
+    const USER_INDEX: usize = 1;
+
+    fn validate_pool(remaining_accounts: &'info [AccountInfo<'info>]) -> Result<Pool> {
+        let pool_account_info = remaining_accounts
+            .first()
+            .ok_or(AgentError::NotPumpswapTrade)?;
+        require!(
+            pool_account_info.owner == &Pumpswap::id(),
+            AgentError::PumpswapPoolInvalid
+        );
+        let pool = {
+            let data = pool_account_info.try_borrow_data()?;
+            require!(
+                data.starts_with(&Pool::DISCRIMINATOR),
+                AgentError::PumpswapPoolInvalid
+            );
+            Pool::deserialize(&mut &data[8..]).map_err(|_| AgentError::PumpswapPoolInvalid)?
+        };
+
+        #[cfg(feature = "enable-log")]
+        msg!("pool {}: {:?}", pool_account_info.key(), pool);
+
+        // graduated pump.fun tokens are quoted in WSOL, trade fee is charged on it
+        require!(
+            pool.quote_mint == spl_token::native_mint::id(),
+            AgentError::NoWsolTokenAccount
+        );
+
+        let pool_base_token_account_info = remaining_accounts
+            .get(7)
+            .ok_or(AgentError::NotPumpswapTrade)?;
+        let pool_quote_token_account_info = remaining_accounts
+            .get(8)
+            .ok_or(AgentError::NotPumpswapTrade)?;
+        require!(
+            pool_base_token_account_info.key() == pool.pool_base_token_account
+                && pool_quote_token_account_info.key() == pool.pool_quote_token_account,
+            AgentError::PumpswapPoolInvalid
+        );
+
+        Ok(pool)
+    }
+
+    fn validate_user_token_account(
+        user_account: &SystemAccount,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        index: usize,
+        mint: Pubkey,
+    ) -> Result<InterfaceAccount<'info, TokenAccount>> {
+        let user_token_account_info = remaining_accounts
+            .get(index)
+            .ok_or(AgentError::NotPumpswapTrade)?;
+        let user_token_account: InterfaceAccount<'info, TokenAccount> =
+            InterfaceAccount::try_from(user_token_account_info)?;
+        require!(
+            user_token_account.owner == user_account.key() && user_token_account.mint == mint,
+            AgentError::PumpswapUserTokenAccountInvalid
+        );
+
+        Ok(user_token_account)
+    }
+
+    pub fn swap_on_pumpswap(
+        ctx: Context<'_, '_, 'info, 'info, SwapOnPumpswap<'info>>,
+        args: SwapOnPumpswapArgs,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.operator_registry.exit_ok(),
+            AgentError::ProgramStatusError
+        );
+        require!(
+            ctx.accounts.operator_registry.feature_ok(FEATURE_PUMPSWAP_SWAP),
+            AgentError::FeaturePaused
+        );
+
+        let remaining_accounts = ctx.remaining_accounts;
+        let user_account = &ctx.accounts.user_account;
+
+        let pool = Self::validate_pool(remaining_accounts)?;
+
+        // validate user account
+        let user_account_info = remaining_accounts
+            .get(Self::USER_INDEX)
+            .ok_or(AgentError::NotPumpswapTrade)?;
+        require!(
+            user_account_info.key() == user_account.key(),
+            AgentError::PumpswapUserAccountInvalid
+        );
+
+        let mut user_base_token_account =
+            Self::validate_user_token_account(user_account, remaining_accounts, 5, pool.base_mint)?;
+        let mut user_quote_token_account =
+            Self::validate_user_token_account(user_account, remaining_accounts, 6, pool.quote_mint)?;
+
+        #[cfg(feature = "enable-log")]
+        msg!("account validation passed!");
+
+        let base_balance_before = user_base_token_account.amount;
+        let quote_balance_before = user_quote_token_account.amount;
+
+        // zero bounds would disable slippage protection
+        let (discriminator, amount, limit) = match args.op {
+            SwapOnPumpswapOp::Buy {
+                base_amount_out,
+                max_quote_amount_in,
+            } => {
+                require!(
+                    base_amount_out > 0 && max_quote_amount_in > 0,
+                    AgentError::PumpswapAmountInvalid
+                );
+                (Self::BUY_DISCRIMINATOR, base_amount_out, max_quote_amount_in)
+            }
+            SwapOnPumpswapOp::Sell {
+                base_amount_in,
+                min_quote_amount_out,
+            } => {
+                require!(
+                    base_amount_in > 0 && min_quote_amount_out > 0,
+                    AgentError::PumpswapAmountInvalid
+                );
+                (Self::SELL_DISCRIMINATOR, base_amount_in, min_quote_amount_out)
+            }
+        };
+
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&discriminator);
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&limit.to_le_bytes());
+
+        let mut accounts: Vec<AccountMeta> = remaining_accounts
+            .iter()
+            .map(|acc| AccountMeta {
+                pubkey: *acc.key,
+                is_signer: acc.is_signer,
+                is_writable: acc.is_writable,
+            })
+            .collect();
+
+        if let Some(signer_account) = accounts.get_mut(Self::USER_INDEX) {
+            signer_account.is_signer = true;
+        }
+
+        let accounts_infos: Vec<AccountInfo> = remaining_accounts
+            .iter()
+            .map(|acc| AccountInfo { ..acc.clone() })
+            .collect();
+
+        let owner_account_key = ctx.accounts.owner_account.key();
+        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
+        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEED_PREFIX,
+            SEED_USER,
+            owner_account_key.as_ref(),
+            user_account_nonce_bytes.as_ref(),
+            user_account_bump_bytes.as_ref(),
+        ]];
+
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.pumpswap_program.key(),
+                accounts,
+                data,
+            },
+            &accounts_infos,
+            signer_seeds,
+        )?;
+
+        user_base_token_account.reload()?;
+        user_quote_token_account.reload()?;
+        let base_balance_after = user_base_token_account.amount;
+        let quote_balance_after = user_quote_token_account.amount;
+
+        // buy spends WSOL for token, sell spends token for WSOL
+        let (input_mint, output_mint, amount_in, amount_out, slippage_ok) = match args.op {
+            SwapOnPumpswapOp::Buy {
+                base_amount_out,
+                max_quote_amount_in,
+            } => {
+                let amount_in = quote_balance_before.saturating_sub(quote_balance_after);
+                let amount_out = base_balance_after.saturating_sub(base_balance_before);
+                (
+                    pool.quote_mint,
+                    pool.base_mint,
+                    amount_in,
+                    amount_out,
+                    amount_in <= max_quote_amount_in && amount_out >= base_amount_out,
+                )
+            }
+            SwapOnPumpswapOp::Sell {
+                base_amount_in,
+                min_quote_amount_out,
+            } => {
+                let amount_in = base_balance_before.saturating_sub(base_balance_after);
+                let amount_out = quote_balance_after.saturating_sub(quote_balance_before);
+                (
+                    pool.base_mint,
+                    pool.quote_mint,
+                    amount_in,
+                    amount_out,
+                    amount_in <= base_amount_in && amount_out >= min_quote_amount_out,
+                )
+            }
+        };
+
+        #[cfg(feature = "enable-log")]
+        msg!("pumpswap: amount_in={} amount_out={}", amount_in, amount_out);
+
+        if !slippage_ok {
+            msg!("pumpswap amounts invalid: in {} out {}", amount_in, amount_out);
+            return err!(AgentError::PumpswapExceedSlippage);
+        }
+
+        let trade_fee = ctx
+            .accounts
+            .fee_registry
+            .calculate_swap_fee(quote_balance_before, quote_balance_after)?;
+
+        // only sells into SOL are allowed in wind down
+        if ctx.accounts.operator_registry.is_wind_down() {
+            require!(quote_balance_after > quote_balance_before, AgentError::WindDownSellOnly);
+        }
+
+        // check operator volume quota
+        ctx.accounts
+            .operator_account
+            .record_volume(quote_balance_after.abs_diff(quote_balance_before))?;
+
+        // record swap fee
+        ctx.accounts
+            .owner_account
+            .add_user_account_due_fee(args.user_account_nonce, trade_fee)?;
+
+        let required_rent: u64 = Rent::get()?.minimum_balance(user_account.data_len());
+        let user_account_balance = user_account.lamports() - required_rent;
+
+        let due_fee = ctx
+            .accounts
+            .owner_account
+            .get_user_account_due_fee(args.user_account_nonce)?;
+        require!(
+            user_account_balance >= due_fee,
+            AgentError::UserAccountBalanceNotEnough
+        );
+
+        emit_cpi!(SwapEvent {
+            owner_account: owner_account_key,
+            user_account: user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            venue_program: ctx.accounts.pumpswap_program.key(),
+            input_mint,
+            output_mint,
+            amount_in,
+            amount_out,
+            trade_fee,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{instruction::Instruction, program::invoke_signed},
+};
+use anchor_spl::{token::Token, token_interface::TokenAccount};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::external_program::RaydiumAmm;
+use crate::state::*;
+
+// accounts of AmmInfo used to validate the pool, AmmInfo has no discriminator
+// and its layout is fixed, so they are read at their offsets
+#[derive(Debug)]
+struct RaydiumAmmInfo {
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_vault_mint: Pubkey,
+    pub pc_vault_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+}
+
+impl RaydiumAmmInfo {
+    const LEN: usize = 752;
+    // 16 u64 parameters, 8 u64 fees and 144 bytes of state data come first
+    const COIN_VAULT_OFFSET: usize = 336;
+
+    fn try_from_account_info(amm: &AccountInfo) -> Result<Self> {
+        require!(amm.owner == &RaydiumAmm::id(), AgentError::RaydiumPoolInvalid);
+
+        let data = amm.try_borrow_data()?;
+        require!(data.len() == Self::LEN, AgentError::RaydiumPoolInvalid);
+
+        let pubkey_at = |index: usize| {
+            let offset = Self::COIN_VAULT_OFFSET + 32 * index;
+            // length is checked above, it would never fail
+            Pubkey::try_from(&data[offset..offset + 32]).unwrap()
+        };
+
+        // coin_vault, pc_vault, coin_vault_mint, pc_vault_mint, lp_mint,
+        // open_orders, market, market_program, target_orders
+        Ok(Self {
+            coin_vault: pubkey_at(0),
+            pc_vault: pubkey_at(1),
+            coin_vault_mint: pubkey_at(2),
+            pc_vault_mint: pubkey_at(3),
+            open_orders: pubkey_at(5),
+            market: pubkey_at(6),
+            market_program: pubkey_at(7),
+            target_orders: pubkey_at(8),
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct SwapOnRaydiumAmmArgs {
+    user_account_nonce: u32,
+    amount_in: u64,
+    min_amount_out: u64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(args: SwapOnRaydiumAmmArgs)]
+pub struct SwapOnRaydiumAmm<'info> {
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_USER, owner_account.key().as_ref(), &args.user_account_nonce.to_le_bytes()],
+        bump
+    )]
+    pub user_account: SystemAccount<'info>,
+    #[account(mut)]
+    pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
+    #[account(
+        mut, // operator swap volume is recorded
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_TRADER) @ AgentError::OperatorNotAllowed
+    )]
+    pub operator_account: Account<'info, OperatorAccount>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_FEE],
+        bump
+    )]
+    pub fee_registry: Account<'info, FeeRegistry>,
+    #[account(
+        mut,
+        token::authority = user_account,
+        constraint = user_source_token_account.key() != user_destination_token_account.key() @ AgentError::RaydiumTokenAccountInvalid
+    )]
+    pub user_source_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::authority = user_account,
+    )]
+    pub user_destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub raydium_amm_program: Program<'info, RaydiumAmm>,
+    /// CHECK: AmmInfo, validated in handler
+    #[account(mut)]
+    pub amm: UncheckedAccount<'info>,
+    /// CHECK: AMM authority
+    #[account(
+        address = RaydiumAmm::AUTHORITY @ AgentError::RaydiumPoolInvalid
+    )]
+    pub amm_authority: UncheckedAccount<'info>,
+    /// CHECK: validated against AmmInfo
+    #[account(mut)]
+    pub amm_open_orders: UncheckedAccount<'info>,
+    /// CHECK: validated against AmmInfo
+    #[account(mut)]
+    pub amm_target_orders: UncheckedAccount<'info>,
+    /// CHECK: validated against AmmInfo
+    #[account(mut)]
+    pub pool_coin_token_account: UncheckedAccount<'info>,
+    /// CHECK: validated against AmmInfo
+    #[account(mut)]
+    pub pool_pc_token_account: UncheckedAccount<'info>,
+    /// CHECK: validated against AmmInfo
+    pub market_program: UncheckedAccount<'info>,
+    /// CHECK: validated against AmmInfo
+    #[account(mut)]
+    pub market: UncheckedAccount<'info>,
+    /// CHECK: validated by the AMM program against market
+    #[account(mut)]
+    pub market_bids: UncheckedAccount<'info>,
+    /// CHECK: validated by the AMM program against market
+    #[account(mut)]
+    pub market_asks: UncheckedAccount<'info>,
+    /// CHECK: validated by the AMM program against market
+    #[account(mut)]
+    pub market_event_queue: UncheckedAccount<'info>,
+    /// CHECK: validated by the AMM program against market
+    #[account(mut)]
+    pub market_coin_vault: UncheckedAccount<'info>,
+    /// CHECK: validated by the AMM program against market
+    #[account(mut)]
+    pub market_pc_vault: UncheckedAccount<'info>,
+    /// CHECK: validated by the AMM program against market
+    pub market_vault_signer: UncheckedAccount<'info>,
+    // AMM v4 pools only hold SPL Token accounts
+    pub token_program: Program<'info, Token>,
+}
+
+impl SwapOnRaydiumAmm<'_> {
+    // SwapBaseIn instruction of AMM v4: tag, amount_in, minimum_amount_out
+    const SWAP_BASE_IN_TAG: u8 = 9;
+
+    fn validate_pool(&self) -> Result<()> {
+        let amm_info = RaydiumAmmInfo::try_from_account_info(&self.amm.to_account_info())?;
+
+        #[cfg(feature = "enable-log")]
+        msg!("amm {}: {:?}", self.amm.key(), amm_info);
+
+        require!(
+            self.amm_open_orders.key() == amm_info.open_orders
+                && self.amm_target_orders.key() == amm_info.target_orders
+                && self.pool_coin_token_account.key() == amm_info.coin_vault
+                && self.pool_pc_token_account.key() == amm_info.pc_vault
+                && self.market_program.key() == amm_info.market_program
+                && self.market.key() == amm_info.market,
+            AgentError::RaydiumPoolInvalid
+        );
+
+        // user token accounts must trade the pool pair, in either direction
+        let source_mint = self.user_source_token_account.mint;
+        let destination_mint = self.user_destination_token_account.mint;
+        require!(
+            (source_mint == amm_info.coin_vault_mint && destination_mint == amm_info.pc_vault_mint)
+                || (source_mint == amm_info.pc_vault_mint
+                    && destination_mint == amm_info.coin_vault_mint),
+            AgentError::RaydiumTokenAccountInvalid
+        );
+
+        Ok(())
+    }
+
+    pub fn swap_on_raydium_amm(
+        ctx: Context<SwapOnRaydiumAmm>,
+        args: SwapOnRaydiumAmmArgs,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.operator_registry.exit_ok(),
+            AgentError::ProgramStatusError
+        );
+        require!(
+            ctx.accounts.operator_registry.feature_ok(FEATURE_RAYDIUM_SWAP),
+            AgentError::FeaturePaused
+        );
+        // a zero min_amount_out would disable slippage protection
+        require!(
+            args.amount_in > 0 && args.min_amount_out > 0,
+            AgentError::RaydiumAmountInvalid
+        );
+
+        ctx.accounts.validate_pool()?;
+
+        #[cfg(feature = "enable-log")]
+        msg!("raydium pool is valid");
+
+        // trade fee is charged on the WSOL side of the swap
+        let source_is_wsol = match (
+            ctx.accounts.user_source_token_account.is_native(),
+            ctx.accounts.user_destination_token_account.is_native(),
+        ) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => return err!(AgentError::NoWsolTokenAccount),
+        };
+
+        let source_balance_before = ctx.accounts.user_source_token_account.amount;
+        let destination_balance_before = ctx.accounts.user_destination_token_account.amount;
+
+        let mut data = Vec::with_capacity(17);
+        data.push(Self::SWAP_BASE_IN_TAG);
+        data.extend_from_slice(&args.amount_in.to_le_bytes());
+        data.extend_from_slice(&args.min_amount_out.to_le_bytes());
+
+        let accounts_infos = vec![
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.amm.to_account_info(),
+            ctx.accounts.amm_authority.to_account_info(),
+            ctx.accounts.amm_open_orders.to_account_info(),
+            ctx.accounts.amm_target_orders.to_account_info(),
+            ctx.accounts.pool_coin_token_account.to_account_info(),
+            ctx.accounts.pool_pc_token_account.to_account_info(),
+            ctx.accounts.market_program.to_account_info(),
+            ctx.accounts.market.to_account_info(),
+            ctx.accounts.market_bids.to_account_info(),
+            ctx.accounts.market_asks.to_account_info(),
+            ctx.accounts.market_event_queue.to_account_info(),
+            ctx.accounts.market_coin_vault.to_account_info(),
+            ctx.accounts.market_pc_vault.to_account_info(),
+            ctx.accounts.market_vault_signer.to_account_info(),
+            ctx.accounts.user_source_token_account.to_account_info(),
+            ctx.accounts.user_destination_token_account.to_account_info(),
+            ctx.accounts.user_account.to_account_info(),
+        ];
+
+        let accounts = vec![
+            AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+            AccountMeta::new(ctx.accounts.amm.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.amm_authority.key(), false),
+            AccountMeta::new(ctx.accounts.amm_open_orders.key(), false),
+            AccountMeta::new(ctx.accounts.amm_target_orders.key(), false),
+            AccountMeta::new(ctx.accounts.pool_coin_token_account.key(), false),
+            AccountMeta::new(ctx.accounts.pool_pc_token_account.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.market_program.key(), false),
+            AccountMeta::new(ctx.accounts.market.key(), false),
+            AccountMeta::new(ctx.accounts.market_bids.key(), false),
+            AccountMeta::new(ctx.accounts.market_asks.key(), false),
+            AccountMeta::new(ctx.accounts.market_event_queue.key(), false),
+            AccountMeta::new(ctx.accounts.market_coin_vault.key(), false),
+            AccountMeta::new(ctx.accounts.market_pc_vault.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.market_vault_signer.key(), false),
+            AccountMeta::new(ctx.accounts.user_source_token_account.key(), false),
+            AccountMeta::new(ctx.accounts.user_destination_token_account.key(), false),
+            // user account signs as the owner of the source token account
+            AccountMeta::new_readonly(ctx.accounts.user_account.key(), true),
+        ];
+
+        let owner_account_key = ctx.accounts.owner_account.key();
+        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
+        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEED_PREFIX,
+            SEED_USER,
+            owner_account_key.as_ref(),
+            user_account_nonce_bytes.as_ref(),
+            user_account_bump_bytes.as_ref(),
+        ]];
+
+        invoke_signed(
+            &Instruction {
+                program_id: ctx.accounts.raydium_amm_program.key(),
+                accounts,
+                data,
+            },
+            &accounts_infos,
+            signer_seeds,
+        )?;
+
+        ctx.accounts.user_source_token_account.reload()?;
+        ctx.accounts.user_destination_token_account.reload()?;
+
+        let amount_in = source_balance_before
+            .saturating_sub(ctx.accounts.user_source_token_account.amount);
+        let amount_out = ctx
+            .accounts
+            .user_destination_token_account
+            .amount
+            .saturating_sub(destination_balance_before);
+
+        #[cfg(feature = "enable-log")]
+        msg!("raydium swap: amount_in={} amount_out={}", amount_in, amount_out);
+
+        if amount_in > args.amount_in || amount_out < args.min_amount_out {
+            msg!(
+                "raydium swap amounts invalid: in {} out {}, min_amount_out {}",
+                amount_in,
+                amount_out,
+                args.min_amount_out
+            );
+            return err!(AgentError::RaydiumExceedSlippage);
+        }
+
+        let (wsol_balance_before, wsol_balance_after) = if source_is_wsol {
+            (source_balance_before, ctx.accounts.user_source_token_account.amount)
+        } else {
+            (destination_balance_before, ctx.accounts.user_destination_token_account.amount)
+        };
+
+        let trade_fee = ctx
+            .accounts
+            .fee_registry
+            .calculate_swap_fee(wsol_balance_before, wsol_balance_after)?;
+
+        // only sells into SOL are allowed in wind down
+        if ctx.accounts.operator_registry.is_wind_down() {
+            require!(wsol_balance_after > wsol_balance_before, AgentError::WindDownSellOnly);
+        }
+
+        // check operator volume quota
+        ctx.accounts
+            .operator_account
+            .record_volume(wsol_balance_after.abs_diff(wsol_balance_before))?;
+
+        // record swap fee
+        ctx.accounts
+            .owner_account
+            .add_user_account_due_fee(args.user_account_nonce, trade_fee)?;
+
+        let user_account = &ctx.accounts.user_account;
+        let required_rent: u64 = Rent::get()?.minimum_balance(user_account.data_len());
+        let user_account_balance = user_account.lamports() - required_rent;
+
+        let due_fee = ctx
+            .accounts
+            .owner_account
+            .get_user_account_due_fee(args.user_account_nonce)?;
+        require!(
+            user_account_balance >= due_fee,
+            AgentError::UserAccountBalanceNotEnough
+        );
+
+        emit_cpi!(SwapEvent {
+            owner_account: owner_account_key,
+            user_account: user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            venue_program: ctx.accounts.raydium_amm_program.key(),
+            input_mint: ctx.accounts.user_source_token_account.mint,
+            output_mint: ctx.accounts.user_destination_token_account.mint,
+            amount_in,
+            amount_out,
+            trade_fee,
+        });
+
+        Ok(())
+    }
+}
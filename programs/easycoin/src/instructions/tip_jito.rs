@@ -1,4 +1,9 @@
-use anchor_lang::{prelude::*, system_program};
+use anchor_lang::{
+    prelude::*,
+    solana_program::sysvar::{self},
+    system_program,
+    Discriminator,
+};
 
 use crate::state::*;
 use crate::errors::*;
@@ -12,6 +17,7 @@ pub struct TipJitoArgs {
     pub tip_amount: u64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: TipJitoArgs)]
 pub struct TipJito<'info> {
@@ -22,10 +28,13 @@ pub struct TipJito<'info> {
     )]
     pub user_account: SystemAccount<'info>, // PDA
     pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
     #[account(
-        constraint = operator_registry.is_operator(operator.key()) @ AgentError::OperatorNotAllowed
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_TIPPER) @ AgentError::OperatorNotAllowed
     )]
-    pub operator: Signer<'info>,
+    pub operator_account: Account<'info, OperatorAccount>,
     #[account(
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
@@ -33,17 +42,56 @@ pub struct TipJito<'info> {
     pub operator_registry: Account<'info, OperatorRegistry>,
     #[account(
         mut,
-        owner = JitoTipProgram::id()
+        owner = JitoTipProgram::id(),
+        constraint = JitoTipProgram::is_tip_account(jito_tip_account.key()) @ AgentError::JitoTipAccountInvalid
     )]
-    /// CHECK: Jito tip account is owned by Jito tip program
+    /// CHECK: Jito tip account is one of the canonical tip accounts of Jito tip program
     pub jito_tip_account: UncheckedAccount<'info>,
+    #[account(
+        address = sysvar::instructions::id() // instructions sysvar
+    )]
+    /// CHECK: instructions_sysvar account
+    pub instructions_sysvar: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
 impl TipJito<'_> {
+    // count the top-level tip_jito instructions in this transaction for the given user account,
+    // user_account is the 1st account of tip_jito
+    fn count_tips_in_transaction(
+        instructions_sysvar: &AccountInfo,
+        user_account: Pubkey,
+    ) -> usize {
+        let mut tips = 0;
+        let mut index = 0;
+        while let Ok(instruction) =
+            sysvar::instructions::load_instruction_at_checked(index, instructions_sysvar)
+        {
+            if instruction.program_id == crate::ID
+                && instruction.data.starts_with(&crate::instruction::TipJito::DISCRIMINATOR)
+                && instruction
+                    .accounts
+                    .first()
+                    .is_some_and(|acc| acc.pubkey == user_account)
+            {
+                tips += 1;
+            }
+            index += 1;
+        }
+        tips
+    }
+
     pub fn tip_jito(ctx: Context<TipJito>, args: TipJitoArgs) -> Result<()> {
 
         require!(ctx.accounts.operator_registry.program_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_JITO_TIP), AgentError::FeaturePaused);
+
+        // at most one tip per user account per transaction
+        let tips = Self::count_tips_in_transaction(
+            ctx.accounts.instructions_sysvar.to_account_info().as_ref(),
+            ctx.accounts.user_account.key(),
+        );
+        require!(tips <= 1, AgentError::JitoTipAlreadyInTransaction);
 
         let owner_account_key = ctx.accounts.owner_account.key();
         let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
@@ -68,8 +116,12 @@ impl TipJito<'_> {
             args.tip_amount,
         )?;
 
-        emit!(TipJitoEvent {
-            user_account: ctx.accounts.user_account.to_account_info().key(),
+        emit_cpi!(TipJitoEvent {
+            owner_account: owner_account_key,
+            user_account: ctx.accounts.user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            jito_tip_account: ctx.accounts.jito_tip_account.key(),
             tip_amount: args.tip_amount,
         });
 
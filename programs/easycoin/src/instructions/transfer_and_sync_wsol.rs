@@ -2,6 +2,7 @@ use anchor_lang::{prelude::*, system_program};
 use anchor_spl::token::{self, spl_token, SyncNative, Token, TokenAccount};
 
 use crate::errors::*;
+use crate::events::*;
 use crate::state::*;
 
 #[derive(AnchorDeserialize, AnchorSerialize)]
@@ -10,6 +11,7 @@ pub struct TransferAndSyncWsolArgs {
     amount: u64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: TransferAndSyncWsolArgs)]
 pub struct TransferAndSyncWsol<'info> {
@@ -27,10 +29,13 @@ pub struct TransferAndSyncWsol<'info> {
     )]
     pub user_account: SystemAccount<'info>,
     pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
     #[account(
-        constraint = operator_registry.is_operator(operator.key()) @ AgentError::OperatorNotAllowed
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_TRADER) @ AgentError::OperatorNotAllowed
     )]
-    pub operator: Signer<'info>,
+    pub operator_account: Account<'info, OperatorAccount>,
     #[account(
         seeds = [SEED_PREFIX, SEED_OPERATOR],
         bump
@@ -83,6 +88,15 @@ impl TransferAndSyncWsol<'_> {
             },
         ))?;
 
+        emit_cpi!(WsolWrappedEvent {
+            owner_account: owner_account_key,
+            user_account: ctx.accounts.user_account.key(),
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            wsol_token_account: ctx.accounts.wsol_token_account.key(),
+            amount: args.amount,
+        });
+
         Ok(())
     }
 }
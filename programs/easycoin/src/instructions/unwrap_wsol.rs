@@ -0,0 +1,235 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{self, spl_token, Mint, Token, TokenAccount};
+
+use crate::errors::*;
+use crate::events::*;
+use crate::state::*;
+
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct UnwrapWsolArgs {
+    user_account_nonce: u32,
+    amount: u64,
+}
+
+// accounts of a partial unwrap, shared with auto-unwrap in swap_on_jupiter
+pub struct WsolUnwrapAccounts<'info> {
+    pub wsol_token_account: AccountInfo<'info>,
+    pub temp_wsol_token_account: AccountInfo<'info>,
+    pub native_mint: AccountInfo<'info>,
+    pub user_account: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+// partial unwrap: amount is moved to a temporary native token account funded by the user account,
+// closing it returns amount plus its rent to the user account, the WSOL token account is kept
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(args: UnwrapWsolArgs)]
+pub struct UnwrapWsol<'info> {
+    #[account(
+        mut,
+        token::mint = spl_token::native_mint::id(),
+        token::authority = user_account,
+    )]
+    pub wsol_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_UNWRAP, user_account.key().as_ref()],
+        bump
+    )]
+    /// CHECK: temporary native token account, created and closed in this instruction
+    pub temp_wsol_token_account: UncheckedAccount<'info>,
+    #[account(
+        address = spl_token::native_mint::id()
+    )]
+    pub native_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [SEED_PREFIX, SEED_USER, owner_account.key().as_ref(), &args.user_account_nonce.to_le_bytes()],
+        bump
+    )]
+    pub user_account: SystemAccount<'info>,
+    pub owner_account: Account<'info, OwnerAccount>,
+    pub operator: Signer<'info>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR, operator.key().as_ref()],
+        bump = operator_account.bump,
+        constraint = operator_account.has_role(ROLE_TRADER) @ AgentError::OperatorNotAllowed
+    )]
+    pub operator_account: Account<'info, OperatorAccount>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_OPERATOR],
+        bump
+    )]
+    pub operator_registry: Account<'info, OperatorRegistry>,
+    pub token_program: Program<'info, Token>, // must be token program, not token-2022 program
+    pub system_program: Program<'info, System>,
+}
+
+impl UnwrapWsol<'_> {
+    // signer_seeds must contain the seeds of both user account and temporary token account
+    pub(crate) fn unwrap_to_user_account<'info>(
+        accounts: &WsolUnwrapAccounts<'info>,
+        signer_seeds: &[&[&[u8]]],
+        amount: u64,
+    ) -> Result<()> {
+        let user_account = accounts.user_account.clone();
+        let temp_wsol_token_account = accounts.temp_wsol_token_account.clone();
+        let system_program = accounts.system_program.clone();
+        let token_program = accounts.token_program.clone();
+
+        // create the temporary token account, the address may have been funded in advance
+        let space = TokenAccount::LEN;
+        let rent = Rent::get()?.minimum_balance(space);
+        let lamports = temp_wsol_token_account.lamports();
+        if lamports == 0 {
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    system_program::CreateAccount {
+                        from: user_account.clone(),
+                        to: temp_wsol_token_account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                rent,
+                space as u64,
+                &token_program.key(),
+            )?;
+        } else {
+            if lamports < rent {
+                system_program::transfer(
+                    CpiContext::new_with_signer(
+                        system_program.clone(),
+                        system_program::Transfer {
+                            from: user_account.clone(),
+                            to: temp_wsol_token_account.clone(),
+                        },
+                        signer_seeds,
+                    ),
+                    rent - lamports,
+                )?;
+            }
+            system_program::allocate(
+                CpiContext::new_with_signer(
+                    system_program.clone(),
+                    system_program::Allocate {
+                        account_to_allocate: temp_wsol_token_account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                space as u64,
+            )?;
+            system_program::assign(
+                CpiContext::new_with_signer(
+                    system_program,
+                    system_program::Assign {
+                        account_to_assign: temp_wsol_token_account.clone(),
+                    },
+                    signer_seeds,
+                ),
+                &token_program.key(),
+            )?;
+        }
+
+        token::initialize_account3(CpiContext::new(
+            token_program.clone(),
+            token::InitializeAccount3 {
+                account: temp_wsol_token_account.clone(),
+                mint: accounts.native_mint.clone(),
+                authority: user_account.clone(),
+            },
+        ))?;
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                token::Transfer {
+                    from: accounts.wsol_token_account.clone(),
+                    to: temp_wsol_token_account.clone(),
+                    authority: user_account.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        // amount plus rent goes back to the user account
+        token::close_account(CpiContext::new_with_signer(
+            token_program,
+            token::CloseAccount {
+                account: temp_wsol_token_account,
+                destination: user_account.clone(),
+                authority: user_account,
+            },
+            signer_seeds,
+        ))?;
+
+        Ok(())
+    }
+
+    pub fn unwrap_wsol(ctx: Context<UnwrapWsol>, args: UnwrapWsolArgs) -> Result<()> {
+
+        // unwrapping is needed to exit in wind down
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        require!(args.amount > 0, AgentError::UnwrapAmountInvalid);
+
+        if ctx.accounts.wsol_token_account.amount < args.amount {
+            msg!(
+                "wsol balance is {}, while amount is {}",
+                ctx.accounts.wsol_token_account.amount,
+                args.amount
+            );
+            return err!(AgentError::UnwrapAmountInvalid);
+        }
+
+        let owner_account_key = ctx.accounts.owner_account.key();
+        let user_account_key = ctx.accounts.user_account.key();
+        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
+        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
+        let temp_bump_bytes = ctx.bumps.temp_wsol_token_account.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                SEED_PREFIX,
+                SEED_USER,
+                owner_account_key.as_ref(),
+                user_account_nonce_bytes.as_ref(),
+                user_account_bump_bytes.as_ref(),
+            ],
+            &[
+                SEED_PREFIX,
+                SEED_UNWRAP,
+                user_account_key.as_ref(),
+                temp_bump_bytes.as_ref(),
+            ],
+        ];
+
+        Self::unwrap_to_user_account(
+            &WsolUnwrapAccounts {
+                wsol_token_account: ctx.accounts.wsol_token_account.to_account_info(),
+                temp_wsol_token_account: ctx.accounts.temp_wsol_token_account.to_account_info(),
+                native_mint: ctx.accounts.native_mint.to_account_info(),
+                user_account: ctx.accounts.user_account.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            signer_seeds,
+            args.amount,
+        )?;
+
+        #[cfg(feature = "enable-log")]
+        msg!("unwrap wsol complete! amount: {}", args.amount);
+
+        emit_cpi!(WsolUnwrappedEvent {
+            owner_account: owner_account_key,
+            user_account: user_account_key,
+            nonce: args.user_account_nonce,
+            operator: ctx.accounts.operator.key(),
+            wsol_token_account: ctx.accounts.wsol_token_account.key(),
+            amount: args.amount,
+        });
+
+        Ok(())
+    }
+}
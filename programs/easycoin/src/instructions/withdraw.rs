@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 
 use crate::errors::*;
+use crate::events::*;
 use crate::state::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
@@ -16,6 +17,7 @@ pub struct WithdrawArgs {
     pub withdraw_op: WithdrawOp,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(args: WithdrawArgs)]
 pub struct Withdraw<'info> {
@@ -39,23 +41,81 @@ pub struct Withdraw<'info> {
         bump
     )]
     pub operator_registry: Account<'info, OperatorRegistry>,
+    // due fee is settled to a registered fee collector before withdrawal,
+    // only required when the user account has due fee
+    #[account(
+        mut,
+        constraint = fee_registry.is_fee_collector(trade_fee_collector.key()) @ AgentError::FeeCollectorInvalid
+    )]
+    pub trade_fee_collector: Option<SystemAccount<'info>>,
+    #[account(
+        seeds = [SEED_PREFIX, SEED_FEE],
+        bump
+    )]
+    pub fee_registry: Account<'info, FeeRegistry>,
 }
 
 impl Withdraw<'_> {
     pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
 
-        require!(ctx.accounts.operator_registry.program_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.exit_ok(), AgentError::ProgramStatusError);
+        require!(ctx.accounts.operator_registry.feature_ok(FEATURE_WITHDRAWAL), AgentError::FeaturePaused);
 
         let owner_account = &mut ctx.accounts.owner_account;
         let user_account = &mut ctx.accounts.user_account;
         let owner = &mut ctx.accounts.owner;
 
+        let owner_account_key = owner_account.key();
+        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
+        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SEED_PREFIX,
+            SEED_USER,
+            owner_account_key.as_ref(),
+            user_account_nonce_bytes.as_ref(),
+            user_account_bump_bytes.as_ref(),
+        ]];
+
+        // settle due fee the operator has not collected yet,
+        // capped by the balance so the owner can always exit.
+        // when the balance is short, all of it is settled and only WithdrawAll can succeed,
+        // which closes the user account and writes off the unpaid remainder, see README
         let due_fee = owner_account.get_user_account_due_fee(args.user_account_nonce)?;
         if due_fee > 0 {
-            // in case there is due fee for user account
-            // if this branch is reached, means operator forgot to collect trade fee
-            msg!("Due fee not paid for user account (nonce {}): {}", args.user_account_nonce, due_fee);
-            return err!(AgentError::DueFeeNotPaid);
+            let trade_fee_collector = ctx
+                .accounts
+                .trade_fee_collector
+                .as_ref()
+                .ok_or(AgentError::FeeCollectorMissing)?;
+            let settled_fee = due_fee.min(user_account.lamports());
+            owner_account.sub_user_account_due_fee(args.user_account_nonce, due_fee)?;
+
+            #[cfg(feature = "enable-log")]
+            msg!("due fee settled for user account (nonce {}): {} of {}", args.user_account_nonce, settled_fee, due_fee);
+
+            if settled_fee > 0 {
+                system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: user_account.to_account_info(),
+                            to: trade_fee_collector.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    settled_fee,
+                )?;
+            }
+
+            emit_cpi!(DueFeeSettledEvent {
+                owner: owner.key(),
+                owner_account: owner_account_key,
+                user_account: user_account.key(),
+                nonce: args.user_account_nonce,
+                fee_collector: trade_fee_collector.key(),
+                due_fee,
+                settled_fee,
+            });
         }
 
         let user_account_lamports = user_account.lamports();
@@ -86,16 +146,6 @@ impl Withdraw<'_> {
         };
 
         // transfer SOL to owner
-        let owner_account_key = owner_account.key();
-        let user_account_nonce_bytes = args.user_account_nonce.to_le_bytes();
-        let user_account_bump_bytes = ctx.bumps.user_account.to_le_bytes();
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            SEED_PREFIX,
-            SEED_USER,
-            owner_account_key.as_ref(),
-            user_account_nonce_bytes.as_ref(),
-            user_account_bump_bytes.as_ref(),
-        ]];
         system_program::transfer(
             CpiContext::new_with_signer(
                 ctx.accounts.system_program.to_account_info(),
@@ -108,9 +158,24 @@ impl Withdraw<'_> {
             withdraw_amount,
         )?;
 
+        emit_cpi!(WithdrawEvent {
+            owner: owner.key(),
+            owner_account: owner_account_key,
+            user_account: user_account.key(),
+            nonce: args.user_account_nonce,
+            amount: withdraw_amount,
+        });
+
         if args.withdraw_op == WithdrawOp::WithdrawAll {
             // remove user account info from owner account
             owner_account.remove_user_account(args.user_account_nonce)?;
+
+            emit_cpi!(UserAccountClosedEvent {
+                owner: owner.key(),
+                owner_account: owner_account_key,
+                user_account: user_account.key(),
+                nonce: args.user_account_nonce,
+            });
         }
 
         Ok(())
@@ -17,36 +17,60 @@ pub mod easycoin {
 
     /******* management related ********/
 
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
-        Initialize::initialize(ctx)
+    pub fn initialize(ctx: Context<Initialize>, args: InitializeArgs) -> Result<()> {
+        Initialize::initialize(ctx, args)
     }
 
-    pub fn manage(ctx: Context<Manage>, op: ManageOp) -> Result<()> {
-        Manage::manage(ctx, op)
+    pub fn queue_proposal(ctx: Context<QueueProposal>, args: QueueProposalArgs) -> Result<()> {
+        QueueProposal::queue_proposal(ctx, args)
     }
 
-    pub fn initialize_fee(ctx: Context<InitializeFee>, fee: InitializeFeeArgs) -> Result<()> {
-        InitializeFee::initialize_fee(ctx, fee)
+    pub fn execute_proposal<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteProposal<'info>>) -> Result<()> {
+        ExecuteProposal::execute_proposal(ctx)
     }
 
-    pub fn manage_fee(ctx: Context<ManageFee>, op: ManageFeeOp) -> Result<()> {
-        ManageFee::manage_fee(ctx, op)
+    pub fn cancel_proposal(ctx: Context<CancelProposal>) -> Result<()> {
+        CancelProposal::cancel_proposal(ctx)
     }
 
-    pub fn initialize_pause(ctx: Context<InitializePause>, pauser: Pubkey) -> Result<()> {
-        InitializePause::initialize_pause(ctx, pauser)
+    pub fn migrate_operator_registry<'info>(ctx: Context<'_, '_, 'info, 'info, MigrateOperatorRegistry<'info>>) -> Result<()> {
+        MigrateOperatorRegistry::migrate_operator_registry(ctx)
     }
 
-    pub fn set_pauser(ctx: Context<SetPauser>, pauser: Pubkey) -> Result<()> {
-        SetPauser::set_pauser(ctx, pauser)
+    pub fn migrate_pause_registry(ctx: Context<MigratePauseRegistry>) -> Result<()> {
+        MigratePauseRegistry::migrate_pause_registry(ctx)
     }
 
-    pub fn pause(ctx: Context<Pause>) -> Result<()> {
-        Pause::pause(ctx)
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        AcceptAuthority::accept_authority(ctx)
     }
 
-    pub fn unpause(ctx: Context<Pause>) -> Result<()> {
-        Pause::unpause(ctx)
+    pub fn accept_pauser(ctx: Context<AcceptPauser>) -> Result<()> {
+        AcceptPauser::accept_pauser(ctx)
+    }
+
+    pub fn pause(ctx: Context<Pause>, duration: i64) -> Result<()> {
+        Pause::pause(ctx, duration)
+    }
+
+    pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
+        Unpause::unpause(ctx)
+    }
+
+    pub fn extend_pause(ctx: Context<Unpause>, duration: i64) -> Result<()> {
+        Unpause::extend_pause(ctx, duration)
+    }
+
+    pub fn pause_features(ctx: Context<Pause>, features: u16, duration: i64) -> Result<()> {
+        Pause::pause_features(ctx, features, duration)
+    }
+
+    pub fn extend_features_pause(ctx: Context<Unpause>, duration: i64) -> Result<()> {
+        Unpause::extend_features_pause(ctx, duration)
+    }
+
+    pub fn unpause_features(ctx: Context<Unpause>, features: u16) -> Result<()> {
+        Unpause::unpause_features(ctx, features)
     }
 
     /******* user management related ********/
@@ -59,6 +83,10 @@ pub mod easycoin {
         CloseOwnerAccount::close_owner_account(ctx)
     }
 
+    pub fn set_owner_swap_limits(ctx: Context<SetOwnerSwapLimits>, max_slippage_bps: u16, max_platform_fee_bps: u16) -> Result<()> {
+        SetOwnerSwapLimits::set_owner_swap_limits(ctx, max_slippage_bps, max_platform_fee_bps)
+    }
+
     /* user account */
     pub fn create_user_account(ctx: Context<CreateUserAccount>, args: CreateUserAccountArgs) -> Result<()> {
         CreateUserAccount::create_user_account(ctx, args)
@@ -68,6 +96,10 @@ pub mod easycoin {
         Withdraw::withdraw(ctx, args)
     }
 
+    pub fn exit_user_account<'info>(ctx: Context<'_, '_, 'info, 'info, ExitUserAccount<'info>>, args: ExitUserAccountArgs) -> Result<()> {
+        ExitUserAccount::exit_user_account(ctx, args)
+    }
+
     /* user token account */
     pub fn create_user_token_account(ctx: Context<CreateUserTokenAccount>, args: CreateUserTokenAccountArgs) -> Result<()> {
         CreateUserTokenAccount::create_user_token_account(ctx, args)
@@ -77,11 +109,19 @@ pub mod easycoin {
         CloseUserTokenAccount::close_user_token_account(ctx, args)
     }
 
+    pub fn owner_close_token_accounts<'info>(ctx: Context<'_, '_, 'info, 'info, OwnerCloseTokenAccounts<'info>>, args: OwnerCloseTokenAccountsArgs) -> Result<()> {
+        OwnerCloseTokenAccounts::owner_close_token_accounts(ctx, args)
+    }
+
     pub fn transfer_and_sync_wsol(ctx: Context<TransferAndSyncWsol>, args: TransferAndSyncWsolArgs) -> Result<()> {
         TransferAndSyncWsol::transfer_and_sync_wsol(ctx, args)
     }
 
-    pub fn swap_on_jupiter<'info>(ctx: Context<'_, '_, 'info, 'info, SwapOnJupiter>, args: SwapOnJupiterArgs) -> Result<()> {
+    pub fn unwrap_wsol(ctx: Context<UnwrapWsol>, args: UnwrapWsolArgs) -> Result<()> {
+        UnwrapWsol::unwrap_wsol(ctx, args)
+    }
+
+    pub fn swap_on_jupiter<'info>(ctx: Context<'_, '_, 'info, 'info, SwapOnJupiter<'info>>, args: SwapOnJupiterArgs) -> Result<()> {
         SwapOnJupiter::swap_on_jupiter(ctx, args)
     }
 
@@ -93,14 +133,32 @@ pub mod easycoin {
         SwapOnPumpfunV2::swap_on_pumpfun_v2(ctx, args)
     }
 
+    pub fn swap_on_pumpswap<'info>(ctx: Context<'_, '_, 'info, 'info, SwapOnPumpswap<'info>>, args: SwapOnPumpswapArgs) -> Result<()> {
+        SwapOnPumpswap::swap_on_pumpswap(ctx, args)
+    }
+
+    pub fn swap_on_raydium_amm(ctx: Context<SwapOnRaydiumAmm>, args: SwapOnRaydiumAmmArgs) -> Result<()> {
+        SwapOnRaydiumAmm::swap_on_raydium_amm(ctx, args)
+    }
+
     pub fn collect_fee(ctx: Context<CollectFee>, args: CollectFeeArgs) -> Result<()> {
         CollectFee::collect_fee(ctx, args)
     }
 
+    pub fn collect_platform_fee(ctx: Context<CollectPlatformFee>) -> Result<()> {
+        CollectPlatformFee::collect_platform_fee(ctx)
+    }
+
     pub fn tip_jito(ctx: Context<TipJito>, args: TipJitoArgs) -> Result<()> {
         TipJito::tip_jito(ctx, args)
     }
 
+    /******* operator related ********/
+
+    pub fn collect_fee_to_operator(ctx: Context<CollectFeeToOperator>, args: CollectFeeToOperatorArgs) -> Result<()> {
+        CollectFeeToOperator::collect_fee_to_operator(ctx, args)
+    }
+
 }
 
 
@@ -12,6 +12,8 @@ use crate::external_program::ComputeBudget;
 pub enum FeeIndex {
     SwapFeeNumerator = 0,
     SwapFeeDenominator,
+    MaxSlippageBps,    // cap of slippage_bps declared by Jupiter routes
+    MaxPlatformFeeBps, // cap of platform_fee_bps declared by Jupiter routes
     FeeIndexInvalid,
 }
 
@@ -20,6 +22,8 @@ impl fmt::Display for FeeIndex {
         match self {
             FeeIndex::SwapFeeNumerator => write!(f, "SwapFeeNumerator"),
             FeeIndex::SwapFeeDenominator => write!(f, "SwapFeeDenominator"),
+            FeeIndex::MaxSlippageBps => write!(f, "MaxSlippageBps"),
+            FeeIndex::MaxPlatformFeeBps => write!(f, "MaxPlatformFeeBps"),
             FeeIndex::FeeIndexInvalid => write!(f, "FeeIndexInvalid"),
         }
     }
@@ -35,7 +39,11 @@ pub struct FeeRegistry {
 
 impl FeeRegistry {
     pub const FEE_INDEX_LEN: usize = FeeIndex::FeeIndexInvalid as usize;
+    // registries created before the slippage and platform fee caps only hold the swap fee,
+    // missing caps read as no cap and are appended when set
+    pub const LEGACY_FEE_INDEX_LEN: usize = FeeIndex::MaxSlippageBps as usize;
     pub const MAX_FEE_COLLECTORS: usize = 16;
+    pub const MAX_BPS: u64 = 10_000;
 
     pub fn size() -> usize {
         8 +  // discriminator
@@ -50,15 +58,19 @@ impl FeeRegistry {
     pub fn get_fee_value(&self, index: FeeIndex) -> Result<u64> {
 
         require!(index != FeeIndex::FeeIndexInvalid, AgentError::FeeIndexInvalid);
-        require!(self.fees.len() == Self::FEE_INDEX_LEN, AgentError::FeesLengthInvalid);
+        require!(
+            (Self::LEGACY_FEE_INDEX_LEN..=Self::FEE_INDEX_LEN).contains(&self.fees.len()),
+            AgentError::FeesLengthInvalid
+        );
 
-        Ok(self.fees[index as usize])
+        Ok(self.fees.get(index as usize).copied().unwrap_or(Self::MAX_BPS))
     }
 
     fn is_legal_fee_value(index: FeeIndex, value: u64) -> bool {
         match index {
             FeeIndex::SwapFeeNumerator => true,
             FeeIndex::SwapFeeDenominator => value != 0,
+            FeeIndex::MaxSlippageBps | FeeIndex::MaxPlatformFeeBps => value <= Self::MAX_BPS,
             _ => false,
         }
     }
@@ -67,9 +79,16 @@ impl FeeRegistry {
     pub fn set_fee_value(&mut self, index: FeeIndex, value: u64) -> Result<()> {
 
         require!(index != FeeIndex::FeeIndexInvalid, AgentError::FeeIndexInvalid);
-        require!(self.fees.len() == Self::FEE_INDEX_LEN, AgentError::FeesLengthInvalid);
+        require!(
+            (Self::LEGACY_FEE_INDEX_LEN..=Self::FEE_INDEX_LEN).contains(&self.fees.len()),
+            AgentError::FeesLengthInvalid
+        );
         require!(Self::is_legal_fee_value(index, value), AgentError::InvalidFeeValue);
 
+        // only caps can be missing, they are filled with no cap up to index
+        if self.fees.len() <= index as usize {
+            self.fees.resize(index as usize + 1, Self::MAX_BPS);
+        }
         self.fees[index as usize] = value;
         msg!("fee set: index={}, value={}", index, value);
 
@@ -97,6 +116,16 @@ impl FeeRegistry {
         return Ok(swap_fee_u128 as u64);
     }
 
+    // a platform fee taken by the venue replaces the swap fee only if it is not lower
+    pub fn platform_fee_covers_swap_fee(&self, platform_fee_bps: u64) -> Result<bool> {
+        let swap_fee_numerator = self.get_fee_value(FeeIndex::SwapFeeNumerator)?;
+        let swap_fee_denominator = self.get_fee_value(FeeIndex::SwapFeeDenominator)?;
+
+        // platform_fee_bps / MAX_BPS >= swap_fee_numerator / swap_fee_denominator
+        Ok(u128::from(platform_fee_bps) * u128::from(swap_fee_denominator)
+            >= u128::from(swap_fee_numerator) * u128::from(Self::MAX_BPS))
+    }
+
     // 1st instruction => SetComputeUnitLimit
     // 2nd instruction => SetComputeUnitPrice
     // otherwise => error
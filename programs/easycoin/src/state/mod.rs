@@ -3,9 +3,15 @@ pub use operator::*;
 pub use fee::*;
 pub use seeds::*;
 pub use pause::*;
+pub use operator_account::*;
+pub use proposal::*;
+pub use owner_swap_limits::*;
 
 mod owner_account;
 mod operator;
 mod seeds;
 mod fee;
-mod pause;
\ No newline at end of file
+mod pause;
+mod operator_account;
+mod proposal;
+mod owner_swap_limits;
\ No newline at end of file
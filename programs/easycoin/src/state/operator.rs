@@ -1,10 +1,30 @@
 use anchor_lang::prelude::*;
 use core::fmt;
-use std::str::FromStr;
 
 use crate::errors::*;
 
-const DEFAULT_REGISTRY_AUTHORITY: &str = "zsWJ5xMC3A5JPJgSaaiHrfVastB8jqGZJjy4X8LTqex";
+const DEFAULT_TIMELOCK_DELAY: i64 = 72 * 60 * 60; // 72 hours
+const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60; // 30 days
+const DEFAULT_MAX_PAUSE_DURATION: i64 = 7 * 24 * 60 * 60; // 7 days
+const MAX_PAUSE_DURATION_LIMIT: i64 = 30 * 24 * 60 * 60; // 30 days
+
+// bits of OperatorRegistry.paused_features
+pub const FEATURE_JUPITER_SWAP: u16 = 1 << 0;
+pub const FEATURE_PUMPFUN_SWAP: u16 = 1 << 1;
+pub const FEATURE_JITO_TIP: u16 = 1 << 2;
+pub const FEATURE_FEE_COLLECTION: u16 = 1 << 3;
+pub const FEATURE_ACCOUNT_CREATION: u16 = 1 << 4;
+pub const FEATURE_WITHDRAWAL: u16 = 1 << 5;
+pub const FEATURE_RAYDIUM_SWAP: u16 = 1 << 6;
+pub const FEATURE_PUMPSWAP_SWAP: u16 = 1 << 7;
+pub const FEATURE_ALL: u16 = FEATURE_JUPITER_SWAP
+    | FEATURE_PUMPFUN_SWAP
+    | FEATURE_RAYDIUM_SWAP
+    | FEATURE_PUMPSWAP_SWAP
+    | FEATURE_JITO_TIP
+    | FEATURE_FEE_COLLECTION
+    | FEATURE_ACCOUNT_CREATION
+    | FEATURE_WITHDRAWAL;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Default)]
 pub enum ProgramStatus {
@@ -12,6 +32,7 @@ pub enum ProgramStatus {
     Uninitialized,
     Initialized,
     Paused,
+    WindDown, // irreversible, only exits are allowed
 }
 
 impl fmt::Display for ProgramStatus {
@@ -20,6 +41,7 @@ impl fmt::Display for ProgramStatus {
             ProgramStatus::Uninitialized => write!(f, "Uninitialized"),
             ProgramStatus::Initialized => write!(f, "Initialized"),
             ProgramStatus::Paused => write!(f, "Paused"),
+            ProgramStatus::WindDown => write!(f, "WindDown"),
         }
     }
 }
@@ -27,13 +49,32 @@ impl fmt::Display for ProgramStatus {
 #[account]
 #[derive(Default)]
 pub struct OperatorRegistry {
+    pub bump: u8,
+    pub program_status: ProgramStatus,
+    pub operator_registry_authority: Pubkey,
+    // M-of-N authority, disabled when authority_threshold is 0
+    pub authority_signers: Vec<Pubkey>,
+    pub authority_threshold: u8,
+    pub pending_authority: Pubkey, // Pubkey::default() if no transfer is pending
+    pub timelock_delay: i64, // seconds between queueing and executing a proposal
+    pub proposal_count: u64, // id of the next proposal
+    pub paused_features: u16, // bitmask of FEATURE_*, paused independently of program_status
+    pub paused_until: i64, // a pause expires at this timestamp unless extended by the authority
+    pub max_pause_duration: i64, // cap of a single pause by a pauser
+    pub features_paused_until: i64, // paused_features are lifted at this timestamp unless extended by the authority
+}
+
+// layout of OperatorRegistry before governance fields and operator accounts were added,
+// only read by migrate_operator_registry
+#[derive(AnchorDeserialize)]
+pub struct LegacyOperatorRegistry {
     pub bump: u8,
     pub program_status: ProgramStatus,
     pub operator_registry_authority: Pubkey,
     pub operators: Vec<Pubkey>,
 }
 
-impl OperatorRegistry {
+impl LegacyOperatorRegistry {
     const MAX_OPERATORS: usize = 32;
 
     pub fn size() -> usize {
@@ -44,63 +85,204 @@ impl OperatorRegistry {
         4       + // operators vector length
         (32 * Self::MAX_OPERATORS) // operators, up to MAX_OPERATORS operators
     }
+}
+
+impl OperatorRegistry {
+    pub const MAX_AUTHORITY_SIGNERS: usize = 10;
+
+    pub fn size() -> usize {
+        8       + // anchor account discriminator
+        1       + // bump
+        1       + // program status
+        32      + // operator_registry_authority
+        4       + // authority_signers vector length
+        (32 * Self::MAX_AUTHORITY_SIGNERS) + // authority_signers, up to MAX_AUTHORITY_SIGNERS signers
+        1       + // authority_threshold
+        32      + // pending_authority
+        8       + // timelock_delay
+        8       + // proposal_count
+        2       + // paused_features
+        8       + // paused_until
+        8       + // max_pause_duration
+        8         // features_paused_until
+    }
+
+    pub fn default_timelock_delay() -> i64 {
+        DEFAULT_TIMELOCK_DELAY
+    }
 
-    pub fn default_registry_authority() -> Pubkey {
-        Pubkey::from_str(DEFAULT_REGISTRY_AUTHORITY).unwrap()
+    pub fn default_max_pause_duration() -> i64 {
+        DEFAULT_MAX_PAUSE_DURATION
+    }
+
+    pub fn set_max_pause_duration(&mut self, duration: i64) -> Result<()> {
+        require!(
+            duration > 0 && duration <= MAX_PAUSE_DURATION_LIMIT,
+            AgentError::PauseDurationInvalid
+        );
+
+        let old_duration = self.max_pause_duration;
+        self.max_pause_duration = duration;
+        msg!("max pause duration set: {} => {}", old_duration, duration);
+        Ok(())
+    }
+
+    pub fn set_timelock_delay(&mut self, delay: i64) -> Result<()> {
+        require!(
+            (0..=MAX_TIMELOCK_DELAY).contains(&delay),
+            AgentError::TimelockDelayInvalid
+        );
+
+        let old_delay = self.timelock_delay;
+        self.timelock_delay = delay;
+        msg!("timelock delay set: {} => {}", old_delay, delay);
+        Ok(())
+    }
+
+    pub fn next_proposal_id(&mut self) -> Result<u64> {
+        let id = self.proposal_count;
+        self.proposal_count = id.checked_add(1).ok_or(AgentError::ProposalIdOverflow)?;
+        Ok(id)
     }
 
     pub fn is_operator_registry_authority(&self, authority: Pubkey) -> bool {
         self.operator_registry_authority == authority
     }
 
-    pub fn transfer_registry_authority(&mut self, new_authority: Pubkey) -> Result<()> {
-        let old_authority = self.operator_registry_authority;
+    // with authority_threshold == 0, operator_registry_authority must sign,
+    // otherwise at least authority_threshold of authority_signers must sign
+    pub fn has_authority_approval(&self, signers: &[Pubkey]) -> bool {
+        if self.authority_threshold == 0 {
+            return signers.contains(&self.operator_registry_authority);
+        }
 
-        self.operator_registry_authority = new_authority;
-        msg!(
-            "operator registry authority transferred: {} => {}",
-            old_authority,
-            new_authority
+        let approvals = self
+            .authority_signers
+            .iter()
+            .filter(|x| signers.contains(x))
+            .count();
+        approvals >= usize::from(self.authority_threshold)
+    }
+
+    // approvals come from the authority signer plus any signer in remaining accounts
+    pub fn check_authority_approval(
+        &self,
+        authority: Pubkey,
+        remaining_accounts: &[AccountInfo],
+    ) -> Result<()> {
+        let mut signers = vec![authority];
+        signers.extend(
+            remaining_accounts
+                .iter()
+                .filter(|acc| acc.is_signer)
+                .map(|acc| acc.key()),
         );
 
+        if !self.has_authority_approval(&signers) {
+            msg!(
+                "authority approval not enough: threshold={}",
+                self.authority_threshold
+            );
+            return err!(AgentError::OperatorRegistryAuthorityNotAllowed);
+        }
         Ok(())
     }
 
-    pub fn add_operator(&mut self, new_operator: Pubkey) -> Result<()> {
-        if self.operators.contains(&new_operator) {
-            msg!("operator already registered before: {}", new_operator);
-            return Ok(());
+    pub fn set_authority_signers(&mut self, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        if signers.len() > Self::MAX_AUTHORITY_SIGNERS {
+            msg!("authority signer limit reached: {}", Self::MAX_AUTHORITY_SIGNERS);
+            return err!(AgentError::AuthoritySignerLimitReached);
         }
 
-        if self.operators.len() >= Self::MAX_OPERATORS {
-            msg!("operator limit reached: {}", Self::MAX_OPERATORS);
-            return err!(AgentError::OperatorLimitReached);
+        for (i, signer) in signers.iter().enumerate() {
+            require!(!signers[..i].contains(signer), AgentError::AuthoritySignersInvalid);
         }
+        // empty signers with threshold 0 falls back to operator_registry_authority
+        require!(
+            usize::from(threshold) <= signers.len() && (threshold > 0 || signers.is_empty()),
+            AgentError::AuthoritySignersInvalid
+        );
 
-        self.operators.push(new_operator);
-        msg!("operator added: {}", new_operator);
+        self.authority_signers = signers;
+        self.authority_threshold = threshold;
+        msg!(
+            "authority signers set: {} signers, threshold {}",
+            self.authority_signers.len(),
+            self.authority_threshold
+        );
         Ok(())
     }
 
-    pub fn remove_operator(&mut self, operator_to_remove: Pubkey) -> Result<()> {
-        if !self.operators.contains(&operator_to_remove) {
-            msg!("operator not registered before: {}", operator_to_remove);
-            return Ok(());
-        }
+    pub fn propose_registry_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        require!(
+            new_authority != self.operator_registry_authority,
+            AgentError::OperatorRegistryAuthorityIsSame
+        );
 
-        self.operators.retain(|x| x != &operator_to_remove);
-        msg!("operator removed: {}", operator_to_remove);
+        self.pending_authority = new_authority;
+        msg!("operator registry authority transfer proposed: {}", new_authority);
         Ok(())
     }
 
-    pub fn is_operator(&self, operator: Pubkey) -> bool {
-        self.operators.contains(&operator)
+    pub fn accept_registry_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        require!(
+            self.pending_authority != Pubkey::default(),
+            AgentError::AuthorityTransferNotPending
+        );
+        require!(
+            self.pending_authority == new_authority,
+            AgentError::PendingAuthorityNotAllowed
+        );
+
+        let old_authority = self.operator_registry_authority;
+
+        self.operator_registry_authority = new_authority;
+        self.pending_authority = Pubkey::default();
+        // the signer set of the old authority is not carried over,
+        // the new authority approves alone until it sets its own
+        self.authority_signers = Vec::new();
+        self.authority_threshold = 0;
+        msg!(
+            "operator registry authority transferred: {} => {}",
+            old_authority,
+            new_authority
+        );
+
+        Ok(())
     }
 
-    pub fn pause(&mut self) -> Result<()> {
-        if self.program_status == ProgramStatus::Initialized {
+    pub fn cancel_registry_authority_transfer(&mut self) -> Result<()> {
+        require!(
+            self.pending_authority != Pubkey::default(),
+            AgentError::AuthorityTransferNotPending
+        );
+
+        msg!("operator registry authority transfer cancelled: {}", self.pending_authority);
+        self.pending_authority = Pubkey::default();
+        Ok(())
+    }
+
+    // an expired pause can be paused again max_pause_duration after it ended,
+    // renewing it earlier takes the authority, see extend_pause
+    pub fn pause(&mut self, duration: i64) -> Result<()> {
+        require!(
+            duration > 0 && duration <= self.max_pause_duration,
+            AgentError::PauseDurationInvalid
+        );
+
+        if self.program_ok() {
+            let now = Clock::get()?.unix_timestamp;
+            let cooldown_end = self.paused_until.saturating_add(self.max_pause_duration);
+            if now < cooldown_end {
+                msg!("pause cooldown until {}", cooldown_end);
+                return err!(AgentError::PauseCooldown);
+            }
+
             self.program_status = ProgramStatus::Paused;
-            msg!("program paused");
+            self.paused_until = now
+                .checked_add(duration)
+                .ok_or(AgentError::PauseDurationInvalid)?;
+            msg!("program paused until {}", self.paused_until);
             Ok(())
         } else {
             msg!("program status error: {}", self.program_status);
@@ -108,8 +290,29 @@ impl OperatorRegistry {
         }
     }
 
+    // only called by the authority, not capped by max_pause_duration
+    pub fn extend_pause(&mut self, duration: i64) -> Result<()> {
+        require!(duration > 0, AgentError::PauseDurationInvalid);
+
+        if self.program_status == ProgramStatus::Paused {
+            self.paused_until = Clock::get()?
+                .unix_timestamp
+                .checked_add(duration)
+                .ok_or(AgentError::PauseDurationInvalid)?;
+            msg!("program pause extended until {}", self.paused_until);
+            Ok(())
+        } else {
+            msg!("program status error: {}", self.program_status);
+            err!(AgentError::ProgramStatusError)
+        }
+    }
+
+    // the cooldown of a pause lifted before it expired starts from now
     pub fn unpause(&mut self) -> Result<()> {
         if self.program_status == ProgramStatus::Paused {
+            if !self.pause_expired() {
+                self.paused_until = Clock::get()?.unix_timestamp;
+            }
             self.program_status = ProgramStatus::Initialized;
             msg!("program unpaused");
             Ok(())
@@ -120,7 +323,116 @@ impl OperatorRegistry {
     }
 
     pub fn program_ok(&self) -> bool {
-        self.program_status == ProgramStatus::Initialized
+        match self.program_status {
+            ProgramStatus::Initialized => true,
+            ProgramStatus::Paused => self.pause_expired(),
+            ProgramStatus::Uninitialized | ProgramStatus::WindDown => false,
+        }
+    }
+
+    // withdrawals, fee settlement, account closing, sells and governance stay available in wind down
+    pub fn exit_ok(&self) -> bool {
+        self.program_ok() || self.is_wind_down()
+    }
+
+    pub fn is_wind_down(&self) -> bool {
+        self.program_status == ProgramStatus::WindDown
+    }
+
+    // irreversible, feature pauses are lifted so that exits can not be blocked
+    pub fn wind_down(&mut self) -> Result<()> {
+        if self.program_ok() {
+            self.program_status = ProgramStatus::WindDown;
+            self.paused_features = 0;
+            msg!("program winding down");
+            Ok(())
+        } else {
+            msg!("program status error: {}", self.program_status);
+            err!(AgentError::ProgramStatusError)
+        }
+    }
+
+    fn pause_expired(&self) -> bool {
+        Clock::get().is_ok_and(|clock| clock.unix_timestamp >= self.paused_until)
+    }
+
+    fn features_pause_expired(&self) -> bool {
+        Clock::get().is_ok_and(|clock| clock.unix_timestamp >= self.features_paused_until)
+    }
+
+    // features paused while a feature pause is active join it and expire with it,
+    // a new feature pause can only start max_pause_duration after the last one ended,
+    // renewing it earlier takes the authority, see extend_features_pause
+    pub fn pause_features(&mut self, features: u16, duration: i64) -> Result<()> {
+        require!(
+            features != 0 && features & !FEATURE_ALL == 0,
+            AgentError::PauseFeaturesInvalid
+        );
+        require!(
+            duration > 0 && duration <= self.max_pause_duration,
+            AgentError::PauseDurationInvalid
+        );
+        require!(!self.is_wind_down(), AgentError::ProgramStatusError);
+
+        let now = Clock::get()?.unix_timestamp;
+        if self.paused_features != 0 && now < self.features_paused_until {
+            self.paused_features |= features;
+        } else {
+            let cooldown_end = self
+                .features_paused_until
+                .saturating_add(self.max_pause_duration);
+            if now < cooldown_end {
+                msg!("features pause cooldown until {}", cooldown_end);
+                return err!(AgentError::PauseCooldown);
+            }
+
+            self.paused_features = features;
+            self.features_paused_until = now
+                .checked_add(duration)
+                .ok_or(AgentError::PauseDurationInvalid)?;
+        }
+
+        msg!(
+            "features paused: {:#06x}, now {:#06x} until {}",
+            features,
+            self.paused_features,
+            self.features_paused_until
+        );
+        Ok(())
+    }
+
+    // only called by the authority, not capped by max_pause_duration
+    pub fn extend_features_pause(&mut self, duration: i64) -> Result<()> {
+        require!(duration > 0, AgentError::PauseDurationInvalid);
+        require!(self.paused_features != 0, AgentError::PauseFeaturesInvalid);
+        require!(!self.is_wind_down(), AgentError::ProgramStatusError);
+
+        self.features_paused_until = Clock::get()?
+            .unix_timestamp
+            .checked_add(duration)
+            .ok_or(AgentError::PauseDurationInvalid)?;
+        msg!(
+            "features {:#06x} pause extended until {}",
+            self.paused_features,
+            self.features_paused_until
+        );
+        Ok(())
+    }
+
+    // lifting every paused feature ends the feature pause, the cooldown starts from now
+    pub fn unpause_features(&mut self, features: u16) -> Result<()> {
+        require!(features & !FEATURE_ALL == 0, AgentError::PauseFeaturesInvalid);
+
+        self.paused_features &= !features;
+        if self.paused_features == 0 && !self.features_pause_expired() {
+            self.features_paused_until = Clock::get()?.unix_timestamp;
+        }
+        msg!("features unpaused: {:#06x}, now {:#06x}", features, self.paused_features);
+        Ok(())
+    }
+
+    pub fn feature_ok(&self, feature: u16) -> bool {
+        self.paused_features & feature == 0 || self.features_pause_expired()
     }
 
 }
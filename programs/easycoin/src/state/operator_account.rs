@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+
+// bits of OperatorAccount.roles
+pub const ROLE_TRADER: u8 = 1 << 0;
+pub const ROLE_FEE_COLLECTOR: u8 = 1 << 1;
+pub const ROLE_ACCOUNT_MAINTAINER: u8 = 1 << 2;
+pub const ROLE_TIPPER: u8 = 1 << 3;
+pub const ROLE_ALL: u8 = ROLE_TRADER | ROLE_FEE_COLLECTOR | ROLE_ACCOUNT_MAINTAINER | ROLE_TIPPER;
+
+// one PDA per operator, an operator is registered iff its PDA exists
+#[account]
+#[derive(Default)]
+pub struct OperatorAccount {
+    pub bump: u8,
+    pub operator: Pubkey,
+    pub roles: u8, // bitmask of ROLE_*
+    pub expires_at: i64, // 0 if never expires
+    pub max_volume_per_epoch: u64, // SOL, 0 if unlimited
+    pub volume_epoch: u64, // epoch of epoch_volume
+    pub epoch_volume: u64, // SOL swapped in volume_epoch
+    pub tx_fee_paid: u64,      // SOL, tx fees paid by the operator
+    pub tx_fee_recovered: u64, // SOL, tx fees reimbursed to the operator
+}
+
+impl OperatorAccount {
+    pub fn size() -> usize {
+        8  + // anchor account discriminator
+        1  + // bump
+        32 + // operator
+        1  + // roles
+        8  + // expires_at
+        8  + // max_volume_per_epoch
+        8  + // volume_epoch
+        8  + // epoch_volume
+        8  + // tx_fee_paid
+        8    // tx_fee_recovered
+    }
+
+    pub fn is_legal_roles(roles: u8) -> bool {
+        roles != 0 && roles & !ROLE_ALL == 0
+    }
+
+    pub fn set_roles(&mut self, roles: u8) -> Result<()> {
+        require!(Self::is_legal_roles(roles), AgentError::OperatorRolesInvalid);
+
+        self.roles = roles;
+        msg!("operator roles set: {}, roles: {:#04x}", self.operator, roles);
+        Ok(())
+    }
+
+    pub fn set_quota(&mut self, expires_at: i64, max_volume_per_epoch: u64) -> Result<()> {
+        self.expires_at = expires_at;
+        self.max_volume_per_epoch = max_volume_per_epoch;
+        msg!(
+            "operator quota set: {}, expires_at: {}, max_volume_per_epoch: {}",
+            self.operator,
+            expires_at,
+            max_volume_per_epoch
+        );
+        Ok(())
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at != 0
+            && Clock::get().map_or(true, |clock| clock.unix_timestamp >= self.expires_at)
+    }
+
+    // expired operators have no role
+    pub fn has_role(&self, role: u8) -> bool {
+        self.roles & role == role && !self.is_expired()
+    }
+
+    // add swap volume of current epoch, fail if max_volume_per_epoch is exceeded
+    pub fn record_volume(&mut self, volume: u64) -> Result<()> {
+        let epoch = Clock::get()?.epoch;
+
+        if self.volume_epoch != epoch {
+            self.volume_epoch = epoch;
+            self.epoch_volume = 0;
+        }
+
+        self.epoch_volume = self
+            .epoch_volume
+            .checked_add(volume)
+            .ok_or(AgentError::OperatorVolumeQuotaExceeded)?;
+
+        if self.max_volume_per_epoch != 0 && self.epoch_volume > self.max_volume_per_epoch {
+            msg!(
+                "operator {} volume quota exceeded: {} > {}",
+                self.operator,
+                self.epoch_volume,
+                self.max_volume_per_epoch
+            );
+            return err!(AgentError::OperatorVolumeQuotaExceeded);
+        }
+
+        Ok(())
+    }
+
+    pub fn record_tx_fee(&mut self, paid: u64, recovered: u64) -> Result<()> {
+        self.tx_fee_paid = self
+            .tx_fee_paid
+            .checked_add(paid)
+            .ok_or(AgentError::OperatorLedgerOverflow)?;
+        self.tx_fee_recovered = self
+            .tx_fee_recovered
+            .checked_add(recovered)
+            .ok_or(AgentError::OperatorLedgerOverflow)?;
+
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "operator {} ledger: tx_fee_paid={} tx_fee_recovered={}",
+            self.operator,
+            self.tx_fee_paid,
+            self.tx_fee_recovered
+        );
+
+        Ok(())
+    }
+}
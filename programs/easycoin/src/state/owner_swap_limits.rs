@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::state::FeeRegistry;
+
+// owner caps on top of the protocol limits, kept out of OwnerAccount so its layout is unchanged,
+// an owner without this account has no cap, a cap of 0 is no cap
+#[account]
+#[derive(Default)]
+pub struct OwnerSwapLimits {
+    pub bump: u8,
+    pub max_slippage_bps: u16,     // cap of slippage_bps declared by Jupiter routes
+    pub max_platform_fee_bps: u16, // cap of platform_fee_bps declared by Jupiter routes
+}
+
+impl OwnerSwapLimits {
+    pub fn size() -> usize {
+        8 + // anchor account discriminator
+        1 + // bump
+        2 + // max_slippage_bps
+        2 // max_platform_fee_bps
+    }
+
+    pub fn set_limits(&mut self, max_slippage_bps: u16, max_platform_fee_bps: u16) -> Result<()> {
+        require!(
+            u64::from(max_slippage_bps) <= FeeRegistry::MAX_BPS,
+            AgentError::SlippageLimitInvalid
+        );
+        require!(
+            u64::from(max_platform_fee_bps) <= FeeRegistry::MAX_BPS,
+            AgentError::PlatformFeeLimitInvalid
+        );
+
+        self.max_slippage_bps = max_slippage_bps;
+        self.max_platform_fee_bps = max_platform_fee_bps;
+        msg!(
+            "owner swap limits set: max slippage {} bps, max platform fee {} bps",
+            max_slippage_bps,
+            max_platform_fee_bps
+        );
+        Ok(())
+    }
+
+    // limits of an owner, read from its swap limits account if it has one
+    pub fn load(owner_swap_limits: &AccountInfo) -> Result<Self> {
+        if owner_swap_limits.owner != &crate::ID {
+            return Ok(Self::default());
+        }
+
+        Self::try_deserialize(&mut &owner_swap_limits.try_borrow_data()?[..])
+    }
+}
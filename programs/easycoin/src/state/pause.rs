@@ -1,27 +1,96 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::*;
+
 #[account]
 #[derive(Default)]
 pub struct PauseRegistry {
+    pub bump: u8,
+    pub pausers: Vec<Pubkey>,
+    pub pending_pauser: Pubkey, // Pubkey::default() if no pauser is pending
+}
+
+// layout of PauseRegistry before multiple pausers were added,
+// only read by migrate_pause_registry
+#[derive(AnchorDeserialize)]
+pub struct LegacyPauseRegistry {
     pub bump: u8,
     pub pauser: Pubkey,
 }
 
-impl PauseRegistry {
+impl LegacyPauseRegistry {
     pub fn size() -> usize {
         8 + // anchor account discriminator
         1 + // bump
         32 // pauser
     }
+}
+
+impl PauseRegistry {
+    pub const MAX_PAUSERS: usize = 8;
+
+    pub fn size() -> usize {
+        8 + // anchor account discriminator
+        1 + // bump
+        4 + // pausers vector length
+        (32 * Self::MAX_PAUSERS) + // pausers, up to MAX_PAUSERS pausers
+        32  // pending_pauser
+    }
+
+    pub fn propose_pauser(&mut self, pauser: Pubkey) -> Result<()> {
+        require!(!self.is_pauser(pauser), AgentError::PauserIsSame);
+
+        if self.pausers.len() >= Self::MAX_PAUSERS {
+            msg!("pauser limit reached: {}", Self::MAX_PAUSERS);
+            return err!(AgentError::PauserLimitReached);
+        }
+
+        self.pending_pauser = pauser;
+        msg!("pauser proposed: {}", pauser);
+        Ok(())
+    }
+
+    pub fn accept_pauser(&mut self, pauser: Pubkey) -> Result<()> {
+        require!(
+            self.pending_pauser != Pubkey::default(),
+            AgentError::PauserTransferNotPending
+        );
+        require!(self.pending_pauser == pauser, AgentError::PendingPauserNotAllowed);
+
+        if self.pausers.len() >= Self::MAX_PAUSERS {
+            msg!("pauser limit reached: {}", Self::MAX_PAUSERS);
+            return err!(AgentError::PauserLimitReached);
+        }
+
+        self.pausers.push(pauser);
+        self.pending_pauser = Pubkey::default();
+        msg!("pauser added: {}", pauser);
+        Ok(())
+    }
+
+    pub fn cancel_pauser_transfer(&mut self) -> Result<()> {
+        require!(
+            self.pending_pauser != Pubkey::default(),
+            AgentError::PauserTransferNotPending
+        );
+
+        msg!("pauser proposal cancelled: {}", self.pending_pauser);
+        self.pending_pauser = Pubkey::default();
+        Ok(())
+    }
+
+    pub fn remove_pauser(&mut self, pauser_to_remove: Pubkey) -> Result<()> {
+        if !self.is_pauser(pauser_to_remove) {
+            msg!("pauser not registered before: {}", pauser_to_remove);
+            return Ok(());
+        }
 
-    pub fn set_pauser(&mut self, pauser: Pubkey) -> Result<()> {
-        let old_pauser = self.pauser;
-        self.pauser = pauser;
-        msg!("pauser set: {} => {}", old_pauser, pauser);
+        self.pausers.retain(|x| x != &pauser_to_remove);
+        msg!("pauser removed: {}", pauser_to_remove);
         Ok(())
     }
 
     pub fn is_pauser(&self, pauser: Pubkey) -> bool {
-        self.pauser == pauser
+        self.pausers.contains(&pauser)
     }
 }
@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::instructions::{ManageFeeOp, ManageOp};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ProposalAction {
+    Manage { op: ManageOp },
+    ManageFee { op: ManageFeeOp },
+    // the new pauser accepts with accept_pauser
+    AddPauser { pauser: Pubkey },
+    CancelPauserTransfer,
+    RemovePauser { pauser: Pubkey },
+}
+
+#[account]
+pub struct Proposal {
+    pub bump: u8,
+    pub id: u64,
+    pub proposer: Pubkey, // rent is returned to proposer once executed or cancelled
+    pub eta: i64,         // earliest execution timestamp
+    pub action: ProposalAction,
+}
+
+impl Proposal {
+    pub fn size(action: &ProposalAction) -> usize {
+        8  + // anchor account discriminator
+        1  + // bump
+        8  + // id
+        32 + // proposer
+        8  + // eta
+        action.try_to_vec().map_or(0, |data| data.len()) // action
+    }
+
+    pub fn is_executable(&self, now: i64) -> bool {
+        now >= self.eta
+    }
+}
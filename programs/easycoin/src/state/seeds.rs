@@ -2,7 +2,10 @@
 pub const SEED_PREFIX: &[u8] = b"easycoin";
 pub const SEED_USER: &[u8] = b"user";   // for user account PDA
 pub const SEED_OWNER: &[u8] = b"owner";  // for owner account PDA
-pub const SEED_OPERATOR: &[u8] = b"operator";  // for owner account PDA
+pub const SEED_OPERATOR: &[u8] = b"operator";  // for operator registry PDA and operator account PDA
 pub const SEED_JITO: &[u8] = b"jito";  // for jito account PDA
 pub const SEED_FEE: &[u8] = b"fee";  // for fee account PDA
-pub const SEED_PAUSE: &[u8] = b"pause";  // for pause account PDA
\ No newline at end of file
+pub const SEED_PAUSE: &[u8] = b"pause";  // for pause account PDA
+pub const SEED_PROPOSAL: &[u8] = b"proposal";  // for timelock proposal PDA
+pub const SEED_UNWRAP: &[u8] = b"unwrap";  // for temporary WSOL token account PDA
+pub const SEED_SWAP_LIMITS: &[u8] = b"swap_limits";  // for owner swap limits PDA
@@ -0,0 +1,38 @@
+#!/usr/bin/env bash
+# runs the test suite against a local validator that also loads the Raydium AMM v4 and OpenBook
+# binaries and the pool dumped into tests/fixtures/raydium, which `anchor test` does not load
+#
+# usage: scripts/test-raydium.sh, after `anchor build`, `yarn dump:raydium-amm` and `yarn dump:raydium-pool`
+set -euo pipefail
+
+cd "$(dirname "$0")/.."
+
+FIXTURES=tests/fixtures/raydium
+WALLET="${ANCHOR_WALLET:-$HOME/.config/solana/id.json}"
+LEDGER=.anchor/test-ledger-raydium
+
+for file in "$FIXTURES/raydium_amm_v4.so" "$FIXTURES/openbook.so" "$FIXTURES/accounts/raydium_amm.json"; do
+  if [ ! -f "$file" ]; then
+    echo "$file not found, run yarn dump:raydium-amm and yarn dump:raydium-pool first" >&2
+    exit 1
+  fi
+done
+
+# the programs of Anchor.toml, plus Raydium and OpenBook
+solana-test-validator --reset --quiet --ledger "$LEDGER" \
+  --upgradeable-program easyTwKoYFtBTzmNqGYjKS5nZ9SvdTkhPxSHbBMnraY target/deploy/easycoin.so "$WALLET" \
+  --bpf-program JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4 target/deploy/mock_jupiter.so \
+  --bpf-program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P target/deploy/mock_pumpfun.so \
+  --bpf-program pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA target/deploy/mock_pumpswap.so \
+  --bpf-program 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8 "$FIXTURES/raydium_amm_v4.so" \
+  --bpf-program srmqPvymJeFKQ4zGQed1GFppgkRHL9kaELCbyksJtPX "$FIXTURES/openbook.so" \
+  --account-dir tests/fixtures/accounts \
+  --account-dir "$FIXTURES/accounts" &
+VALIDATOR=$!
+trap 'kill $VALIDATOR' EXIT
+
+until solana cluster-version -u localhost >/dev/null 2>&1; do
+  sleep 1
+done
+
+anchor test --skip-local-validator --skip-deploy --skip-build
@@ -1,16 +1,2084 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import { ComputeBudgetProgram, Keypair, LAMPORTS_PER_SOL, PublicKey, SystemProgram } from "@solana/web3.js";
+import { expect } from "chai";
 import { Easycoin } from "../target/types/easycoin";
 
 describe("easycoin", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.Easycoin as Program<Easycoin>;
 
+  const pda = (...seeds: (Buffer | Uint8Array)[]) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("easycoin"), ...seeds],
+      program.programId
+    )[0];
+
+  const nonceBytes = (nonce: number) => {
+    const bytes = Buffer.alloc(4);
+    bytes.writeUInt32LE(nonce);
+    return bytes;
+  };
+
+  const ownerAccountPda = (owner: PublicKey) =>
+    pda(Buffer.from("owner"), owner.toBuffer());
+
+  const userAccountPda = (ownerAccount: PublicKey, nonce: number) =>
+    pda(Buffer.from("user"), ownerAccount.toBuffer(), nonceBytes(nonce));
+
+  // fails the test unless the promise rejects with the given AgentError
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (e) {
+      expect(e.error?.errorCode?.code ?? e.toString()).to.contain(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const fund = async (to: PublicKey, lamports: number) => {
+    const tx = new anchor.web3.Transaction().add(
+      SystemProgram.transfer({
+        fromPubkey: provider.wallet.publicKey,
+        toPubkey: to,
+        lamports,
+      })
+    );
+    await provider.sendAndConfirm(tx);
+  };
+
+  const sleep = (seconds: number) =>
+    new Promise((resolve) => setTimeout(resolve, seconds * 1000));
+
+  const FEATURE_ACCOUNT_CREATION = 1 << 4;
+
+  const ROLE_TRADER = 1 << 0;
+  const ROLE_FEE_COLLECTOR = 1 << 1;
+  const ROLE_ACCOUNT_MAINTAINER = 1 << 2;
+  const ROLE_TIPPER = 1 << 3;
+
+  const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+  const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+  const NATIVE_MINT = new PublicKey("So11111111111111111111111111111111111111112");
+
+  const associatedTokenAddress = (owner: PublicKey, mint: PublicKey, tokenProgram = TOKEN_PROGRAM_ID) =>
+    PublicKey.findProgramAddressSync(
+      [owner.toBuffer(), tokenProgram.toBuffer(), mint.toBuffer()],
+      ASSOCIATED_TOKEN_PROGRAM_ID
+    )[0];
+
+  const authority = provider.wallet.publicKey;
+  const operatorRegistry = pda(Buffer.from("operator"));
+
+  const proposalPda = (id: anchor.BN) =>
+    pda(Buffer.from("proposal"), id.toArrayLike(Buffer, "le", 8));
+
+  const operatorAccountPda = (operator: PublicKey) =>
+    pda(Buffer.from("operator"), operator.toBuffer());
+
+  const asRemaining = (pubkeys: PublicKey[], isWritable = true) =>
+    pubkeys.map((pubkey) => ({ pubkey, isSigner: false, isWritable }));
+
+  // other authority signers approve as signing remaining accounts
+  const asApprovals = (signers: Keypair[]) =>
+    signers.map((signer) => ({ pubkey: signer.publicKey, isSigner: true, isWritable: false }));
+
+  const queueProposal = async (action, approvals: Keypair[] = []) => {
+    const registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+    const id = registry.proposalCount;
+    await program.methods
+      .queueProposal({ action })
+      .accountsPartial({
+        proposal: proposalPda(id),
+        operatorRegistryAuthority: authority,
+      })
+      .remainingAccounts(asApprovals(approvals))
+      .signers(approvals)
+      .rpc();
+    return id;
+  };
+
+  const executeProposal = (id: anchor.BN, remainingAccounts: PublicKey[] = []) =>
+    program.methods
+      .executeProposal()
+      .accountsPartial({
+        proposal: proposalPda(id),
+        proposer: authority,
+      })
+      .remainingAccounts(asRemaining(remainingAccounts))
+      .rpc();
+
+  const cancelProposal = (id: anchor.BN) =>
+    program.methods
+      .cancelProposal()
+      .accountsPartial({
+        proposal: proposalPda(id),
+        proposer: authority,
+        operatorRegistryAuthority: authority,
+      })
+      .rpc();
+
+  // tests run with a zero timelock delay, so proposals execute right away
+  const propose = async (action, remainingAccounts: PublicKey[] = [], approvals: Keypair[] = []) => {
+    const id = await queueProposal(action, approvals);
+    await executeProposal(id, remainingAccounts);
+  };
+
+  const manage = (op) => ({ manage: { op } });
+
+  const pauseFeatures = (features: number, duration: number) =>
+    program.methods
+      .pauseFeatures(features, new anchor.BN(duration))
+      .accounts({ pauser: authority })
+      .rpc();
+
+  const unpauseFeatures = (features: number) =>
+    program.methods
+      .unpauseFeatures(features)
+      .accountsPartial({ operatorRegistryAuthority: authority })
+      .rpc();
+
+  // events emitted through emit_cpi, in order
+  const cpiEvents = async (signature: string) => {
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const accountKeys = tx.transaction.message.getAccountKeys();
+    return tx.meta.innerInstructions
+      .flatMap((inner) => inner.instructions)
+      .filter((ix) => accountKeys.get(ix.programIdIndex).equals(program.programId))
+      .map((ix) => {
+        // skip the event instruction tag
+        const data = anchor.utils.bytes.bs58.decode(ix.data).subarray(8);
+        return program.coder.events.decode(anchor.utils.bytes.base64.encode(Buffer.from(data)));
+      })
+      .filter((event) => event !== null);
+  };
+
+  // an owner with one user account holding `lamports`
+  const createUser = async (lamports: number, nonce = 0) => {
+    const owner = Keypair.generate();
+    await fund(owner.publicKey, LAMPORTS_PER_SOL);
+
+    await program.methods
+      .createOwnerAccount()
+      .accounts({ owner: owner.publicKey })
+      .signers([owner])
+      .rpc();
+    const ownerAccount = ownerAccountPda(owner.publicKey);
+
+    await program.methods
+      .createUserAccount({ nonce })
+      .accounts({ owner: owner.publicKey })
+      .signers([owner])
+      .rpc();
+    const userAccount = userAccountPda(ownerAccount, nonce);
+    await fund(userAccount, lamports);
+
+    return { owner, ownerAccount, userAccount, nonce };
+  };
+
+  // registers a new operator with the given roles
+  const addOperator = async (roles: number) => {
+    const operator = Keypair.generate();
+    await propose(manage({ addOperator: { operators: [operator.publicKey], roles } }), [
+      operatorAccountPda(operator.publicKey),
+    ]);
+    return operator;
+  };
+
+  const setOperatorQuota = (operator: PublicKey, expiresAt: number, maxVolumePerEpoch: number) =>
+    propose(
+      manage({
+        setOperatorQuota: {
+          operators: [operator],
+          expiresAt: new anchor.BN(expiresAt),
+          maxVolumePerEpoch: new anchor.BN(maxVolumePerEpoch),
+        },
+      }),
+      [operatorAccountPda(operator)]
+    );
+
+  // creates the associated token account of a user account
+  const createUserTokenAccount = async (user, maintainer: Keypair, mint: PublicKey) => {
+    const tokenAccount = associatedTokenAddress(user.userAccount, mint);
+    await program.methods
+      .createUserTokenAccount({ userAccountNonce: user.nonce })
+      .accountsPartial({
+        tokenAccount,
+        mint,
+        userAccount: user.userAccount,
+        ownerAccount: user.ownerAccount,
+        operator: maintainer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([maintainer])
+      .rpc();
+    return tokenAccount;
+  };
+
+  const createWsolAccount = (user, maintainer: Keypair) =>
+    createUserTokenAccount(user, maintainer, NATIVE_MINT);
+
+  // SPL Token and associated token account instructions, the provider wallet pays and is the mint authority
+  const createMint = async (decimals = 6) => {
+    const mint = Keypair.generate();
+    const space = 82;
+    const tx = new anchor.web3.Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: authority,
+        newAccountPubkey: mint.publicKey,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+        space,
+        programId: TOKEN_PROGRAM_ID,
+      }),
+      new anchor.web3.TransactionInstruction({
+        programId: TOKEN_PROGRAM_ID,
+        keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+        // InitializeMint2, without freeze authority
+        data: Buffer.concat([Buffer.from([20, decimals]), authority.toBuffer(), Buffer.from([0])]),
+      })
+    );
+    await provider.sendAndConfirm(tx, [mint]);
+    return mint.publicKey;
+  };
+
+  // creates the associated token account of any owner, PDAs included
+  const createTokenAccount = async (owner: PublicKey, mint: PublicKey) => {
+    const tokenAccount = associatedTokenAddress(owner, mint);
+    const tx = new anchor.web3.Transaction().add(
+      new anchor.web3.TransactionInstruction({
+        programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+        keys: [
+          { pubkey: authority, isSigner: true, isWritable: true },
+          { pubkey: tokenAccount, isSigner: false, isWritable: true },
+          { pubkey: owner, isSigner: false, isWritable: false },
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+          { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        // CreateIdempotent
+        data: Buffer.from([1]),
+      })
+    );
+    await provider.sendAndConfirm(tx);
+    return tokenAccount;
+  };
+
+  // mints tokens, or wraps lamports into a WSOL token account
+  const fundTokenAccount = async (tokenAccount: PublicKey, mint: PublicKey, amount: number) => {
+    const amountBytes = new anchor.BN(amount).toArrayLike(Buffer, "le", 8);
+    const tx = new anchor.web3.Transaction();
+    if (mint.equals(NATIVE_MINT)) {
+      tx.add(
+        SystemProgram.transfer({ fromPubkey: authority, toPubkey: tokenAccount, lamports: amount }),
+        new anchor.web3.TransactionInstruction({
+          programId: TOKEN_PROGRAM_ID,
+          keys: [{ pubkey: tokenAccount, isSigner: false, isWritable: true }],
+          // SyncNative
+          data: Buffer.from([17]),
+        })
+      );
+    } else {
+      tx.add(
+        new anchor.web3.TransactionInstruction({
+          programId: TOKEN_PROGRAM_ID,
+          keys: [
+            { pubkey: mint, isSigner: false, isWritable: true },
+            { pubkey: tokenAccount, isSigner: false, isWritable: true },
+            { pubkey: authority, isSigner: true, isWritable: false },
+          ],
+          // MintTo
+          data: Buffer.concat([Buffer.from([7]), amountBytes]),
+        })
+      );
+    }
+    await provider.sendAndConfirm(tx);
+  };
+
+  const tokenBalance = async (tokenAccount: PublicKey) =>
+    Number((await provider.connection.getTokenAccountBalance(tokenAccount)).value.amount);
+
+  // moves lamports of a user account into its WSOL token account
+  const wrapSol = (user, wsolTokenAccount: PublicKey, trader: Keypair, amount: number) =>
+    program.methods
+      .transferAndSyncWsol({ userAccountNonce: user.nonce, amount: new anchor.BN(amount) })
+      .accountsPartial({
+        wsolTokenAccount,
+        userAccount: user.userAccount,
+        ownerAccount: user.ownerAccount,
+        operator: trader.publicKey,
+      })
+      .signers([trader])
+      .rpc();
+
+  // Jupiter routes run against the mock Jupiter program in tests/programs/mock_jupiter,
+  // it fills routes from its own vault token accounts, appended after the route accounts
+  const JUPITER_PROGRAM_ID = new PublicKey("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+  const JUPITER_ROUTE = [229, 23, 203, 151, 122, 227, 173, 42];
+  const JUPITER_SHARED_ACCOUNTS_ROUTE = [193, 32, 155, 51, 65, 214, 156, 129];
+  const JUPITER_EXACT_OUT_ROUTE = [208, 51, 239, 151, 123, 43, 237, 92];
+
+  const writable = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+  const readonly = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: false });
+
+  // route instruction data, borsh encoded like the Jupiter IDL, the route plan holds no step unless
+  // chargedPlatformFeeBps or swapVariant is set, the mock Jupiter program then charges the percent of the step
+  // as platform fee bps, swapVariant sets a swap variant without fields, unknown to the IDL above its variants
+  const jupiterData = (
+    discriminator: number[],
+    {
+      amount,
+      quotedAmount,
+      slippageBps = 100,
+      platformFeeBps = 0,
+      id = undefined as number | undefined,
+      chargedPlatformFeeBps = undefined as number | undefined,
+      swapVariant = undefined as number | undefined,
+    }
+  ) => {
+    const tail = Buffer.alloc(19);
+    tail.writeBigUInt64LE(BigInt(amount), 0);
+    tail.writeBigUInt64LE(BigInt(quotedAmount), 8);
+    tail.writeUInt16LE(slippageBps, 16);
+    tail.writeUInt8(platformFeeBps, 18);
+    const routePlan =
+      chargedPlatformFeeBps === undefined && swapVariant === undefined
+        ? Buffer.from([0, 0, 0, 0])
+        : Buffer.from([1, 0, 0, 0, swapVariant ?? 0, chargedPlatformFeeBps ?? 100, 0, 1]);
+    return Buffer.concat([
+      Buffer.from(discriminator),
+      Buffer.from(id === undefined ? [] : [id]),
+      routePlan,
+      tail,
+    ]);
+  };
+
+  // one token market shared by all Jupiter tests
+  let jupiterMarket: { vaultAuthority: PublicKey; mint: PublicKey; tokenVault: PublicKey; wsolVault: PublicKey };
+  const setupJupiterMarket = async () => {
+    if (!jupiterMarket) {
+      const vaultAuthority = PublicKey.findProgramAddressSync(
+        [Buffer.from("vault_authority")],
+        JUPITER_PROGRAM_ID
+      )[0];
+      const mint = await createMint();
+      const tokenVault = await createTokenAccount(vaultAuthority, mint);
+      await fundTokenAccount(tokenVault, mint, 1_000_000_000_000);
+      const wsolVault = await createTokenAccount(vaultAuthority, NATIVE_MINT);
+      await fundTokenAccount(wsolVault, NATIVE_MINT, 10 * LAMPORTS_PER_SOL);
+      jupiterMarket = { vaultAuthority, mint, tokenVault, wsolVault };
+    }
+    return jupiterMarket;
+  };
+
+  // a user account with WSOL and token accounts, and an operator to trade for it
+  const createSwapUser = async (lamports: number) => {
+    const { mint } = await setupJupiterMarket();
+    const user = await createUser(lamports);
+    const operator = await addOperator(ROLE_TRADER | ROLE_ACCOUNT_MAINTAINER);
+    const wsolTokenAccount = await createWsolAccount(user, operator);
+    const tokenAccount = await createUserTokenAccount(user, operator, mint);
+    return { ...user, operator, wsolTokenAccount, tokenAccount };
+  };
+
+  // remaining accounts of a Jupiter route buying (SOL to token) or selling (token to SOL)
+  const jupiterRouteAccounts = (
+    user,
+    buy: boolean,
+    {
+      route = JUPITER_ROUTE,
+      id = 0,
+      platformFeeAccount = JUPITER_PROGRAM_ID,
+      destinationTokenAccount = undefined as PublicKey | undefined,
+    } = {}
+  ) => {
+    const { vaultAuthority, mint, tokenVault, wsolVault } = jupiterMarket;
+    const [source, destination] = buy
+      ? [user.wsolTokenAccount, user.tokenAccount]
+      : [user.tokenAccount, user.wsolTokenAccount];
+    const [sourceMint, destinationMint] = buy ? [NATIVE_MINT, mint] : [mint, NATIVE_MINT];
+    const [sourceVault, destinationVault] = buy ? [wsolVault, tokenVault] : [tokenVault, wsolVault];
+
+    let accounts;
+    if (route === JUPITER_SHARED_ACCOUNTS_ROUTE) {
+      const programAuthority = PublicKey.findProgramAddressSync(
+        [Buffer.from("authority"), Buffer.from([id])],
+        JUPITER_PROGRAM_ID
+      )[0];
+      accounts = [
+        readonly(TOKEN_PROGRAM_ID),
+        readonly(programAuthority),
+        writable(user.userAccount),
+        writable(source),
+        writable(sourceVault),
+        writable(destinationVault),
+        writable(destination),
+        readonly(sourceMint),
+        readonly(destinationMint),
+        writable(platformFeeAccount),
+        readonly(JUPITER_PROGRAM_ID), // no token 2022 program
+      ];
+    } else {
+      accounts = [
+        readonly(TOKEN_PROGRAM_ID),
+        writable(user.userAccount),
+        writable(source),
+        writable(destination),
+        writable(destinationTokenAccount ?? destination),
+        ...(route === JUPITER_EXACT_OUT_ROUTE ? [readonly(sourceMint)] : []),
+        readonly(destinationMint),
+        writable(platformFeeAccount),
+        ...(route === JUPITER_EXACT_OUT_ROUTE ? [readonly(JUPITER_PROGRAM_ID)] : []), // no token 2022 program
+      ];
+    }
+    accounts
+      .filter((meta) => meta.pubkey.equals(JUPITER_PROGRAM_ID))
+      .forEach((meta) => (meta.isWritable = false));
+
+    return [
+      ...accounts,
+      readonly(JUPITER_PROGRAM_ID), // event authority
+      readonly(JUPITER_PROGRAM_ID), // program
+      readonly(vaultAuthority),
+      writable(sourceVault),
+      writable(destinationVault),
+    ];
+  };
+
+  const swapOnJupiter = (user, jupiterData: Buffer, remainingAccounts, autoWrapWsol = false, accounts = {}) =>
+    program.methods
+      .swapOnJupiter({ userAccountNonce: user.nonce, jupiterData, autoWrapWsol })
+      .accountsPartial({
+        userAccount: user.userAccount,
+        ownerAccount: user.ownerAccount,
+        operator: user.operator.publicKey,
+        jupiterProgram: JUPITER_PROGRAM_ID,
+        tempWsolTokenAccount: autoWrapWsol ? pda(Buffer.from("unwrap"), user.userAccount.toBuffer()) : null,
+        nativeMint: autoWrapWsol ? NATIVE_MINT : null,
+        tokenProgram: autoWrapWsol ? TOKEN_PROGRAM_ID : null,
+        systemProgram: autoWrapWsol ? SystemProgram.programId : null,
+        ...accounts,
+      })
+      .remainingAccounts(remainingAccounts)
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+      .signers([user.operator])
+      .rpc();
+
+  const dueFee = async (user) => {
+    const ownerAccount = await program.account.ownerAccount.fetch(user.ownerAccount);
+    return ownerAccount.createdUserAccounts.find((info) => info.nonce === user.nonce).dueFee.toNumber();
+  };
+
+  const initializeArgs = {
+    operatorRegistryAuthority: provider.wallet.publicKey,
+    pauser: provider.wallet.publicKey,
+    swapFeeNumerator: new anchor.BN(1),
+    swapFeeDenominator: new anchor.BN(100),
+    maxSlippageBps: new anchor.BN(500),
+    maxPlatformFeeBps: new anchor.BN(0),
+    timelockDelay: new anchor.BN(0),
+  };
+
+  it("rejects initialize by anyone but the upgrade authority", async () => {
+    const other = Keypair.generate();
+    await fund(other.publicKey, LAMPORTS_PER_SOL);
+
+    await expectError(
+      program.methods
+        .initialize(initializeArgs)
+        .accounts({ upgradeAuthority: other.publicKey })
+        .signers([other])
+        .rpc(),
+      "UpgradeAuthorityNotAllowed"
+    );
+  });
+
+  it("rejects initialize without a pauser", async () => {
+    await expectError(
+      program.methods
+        .initialize({ ...initializeArgs, pauser: PublicKey.default })
+        .accounts({ upgradeAuthority: provider.wallet.publicKey })
+        .rpc(),
+      "InitializeArgsInvalid"
+    );
+  });
+
   it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
+    // the provider wallet deploys the program, so it is the upgrade authority
+    const tx = await program.methods
+      .initialize(initializeArgs)
+      .accounts({
+        upgradeAuthority: provider.wallet.publicKey,
+      })
+      .rpc();
     console.log("Your transaction signature", tx);
   });
+
+  describe("withdraw", () => {
+    const amountIn = LAMPORTS_PER_SOL / 10;
+    const tokens = 1_000_000;
+
+    let tradeFeeCollector: PublicKey;
+
+    before(async () => {
+      tradeFeeCollector = Keypair.generate().publicKey;
+      await fund(tradeFeeCollector, LAMPORTS_PER_SOL);
+      await propose({ manageFee: { op: { addFeeCollector: { feeCollectors: [tradeFeeCollector] } } } });
+    });
+
+    // a user account that bought tokens with amountIn, so amountIn / 100 is due
+    const createUserWithDueFee = async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens }),
+        jupiterRouteAccounts(user, true),
+        true
+      );
+      expect(await dueFee(user)).to.equal(amountIn / 100);
+      return user;
+    };
+
+    const withdraw = (user, withdrawOp, feeCollector: PublicKey | null) =>
+      program.methods
+        .withdraw({ userAccountNonce: user.nonce, withdrawOp })
+        .accountsPartial({
+          owner: user.owner.publicKey,
+          tradeFeeCollector: feeCollector,
+        })
+        .signers([user.owner])
+        .rpc();
+
+    it("withdraws without a fee collector when no fee is due", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      const before = await provider.connection.getBalance(user.userAccount);
+
+      await program.methods
+        .withdraw({
+          userAccountNonce: user.nonce,
+          withdrawOp: { withdraw: { amount: new anchor.BN(LAMPORTS_PER_SOL / 20) } },
+        })
+        .accountsPartial({
+          owner: user.owner.publicKey,
+          tradeFeeCollector: null,
+        })
+        .signers([user.owner])
+        .rpc();
+
+      const after = await provider.connection.getBalance(user.userAccount);
+      expect(before - after).to.equal(LAMPORTS_PER_SOL / 20);
+    });
+
+    it("rejects a fee collector that is not registered", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+
+      await expectError(
+        program.methods
+          .withdraw({
+            userAccountNonce: user.nonce,
+            withdrawOp: { withdrawAll: {} },
+          })
+          .accountsPartial({
+            owner: user.owner.publicKey,
+            tradeFeeCollector: Keypair.generate().publicKey,
+          })
+          .signers([user.owner])
+          .rpc(),
+        "FeeCollectorInvalid"
+      );
+    });
+
+    it("settles the due fee in full before the withdrawal", async () => {
+      const user = await createUserWithDueFee();
+      const collectorBefore = await provider.connection.getBalance(tradeFeeCollector);
+      const before = await provider.connection.getBalance(user.userAccount);
+
+      await withdraw(user, { withdraw: { amount: new anchor.BN(amountIn) } }, tradeFeeCollector);
+
+      const collectorAfter = await provider.connection.getBalance(tradeFeeCollector);
+      const after = await provider.connection.getBalance(user.userAccount);
+      expect(collectorAfter - collectorBefore).to.equal(amountIn / 100);
+      expect(before - after).to.equal(amountIn + amountIn / 100);
+      expect(await dueFee(user)).to.equal(0);
+    });
+
+    it("settles the balance short of the due fee and writes off the rest on close", async () => {
+      const user = await createUserWithDueFee();
+      // leave a rent exempt balance below the due fee
+      const balance = 900_000;
+      await wrapSol(
+        user,
+        user.wsolTokenAccount,
+        user.operator,
+        (await provider.connection.getBalance(user.userAccount)) - balance
+      );
+      const collectorBefore = await provider.connection.getBalance(tradeFeeCollector);
+
+      // nothing is left to withdraw an amount from
+      await expectError(
+        withdraw(user, { withdraw: { amount: new anchor.BN(1) } }, tradeFeeCollector),
+        "UserAccountBalanceNotEnough"
+      );
+      await withdraw(user, { withdrawAll: {} }, tradeFeeCollector);
+
+      const collectorAfter = await provider.connection.getBalance(tradeFeeCollector);
+      expect(collectorAfter - collectorBefore).to.equal(balance);
+      const ownerAccount = await program.account.ownerAccount.fetch(user.ownerAccount);
+      expect(ownerAccount.createdUserAccounts).to.be.empty;
+    });
+
+    it("rejects a withdrawal without a fee collector when fee is due", async () => {
+      const user = await createUserWithDueFee();
+
+      await expectError(withdraw(user, { withdrawAll: {} }, null), "FeeCollectorMissing");
+    });
+  });
+
+  describe("exit_user_account", () => {
+    const amountIn = LAMPORTS_PER_SOL / 10;
+    const tokens = 1_000_000;
+
+    let tradeFeeCollector: PublicKey;
+
+    before(async () => {
+      tradeFeeCollector = Keypair.generate().publicKey;
+      await fund(tradeFeeCollector, LAMPORTS_PER_SOL);
+      await propose({ manageFee: { op: { addFeeCollector: { feeCollectors: [tradeFeeCollector] } } } });
+    });
+
+    // token accounts of the user account are passed with their mint and the owner's ATA of the mint
+    const exitUserAccount = (user, feeCollector: PublicKey | null, tokenAccounts: [PublicKey, PublicKey][] = []) =>
+      program.methods
+        .exitUserAccount({ userAccountNonce: user.nonce })
+        .accountsPartial({
+          owner: user.owner.publicKey,
+          tradeFeeCollector: feeCollector,
+        })
+        .remainingAccounts(
+          tokenAccounts.flatMap(([tokenAccount, mint]) => [
+            writable(tokenAccount),
+            readonly(mint),
+            writable(associatedTokenAddress(user.owner.publicKey, mint)),
+          ])
+        )
+        .signers([user.owner])
+        .rpc();
+
+    it("exits without a fee collector when no fee is due", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+
+      await program.methods
+        .exitUserAccount({ userAccountNonce: user.nonce })
+        .accountsPartial({
+          owner: user.owner.publicKey,
+          tradeFeeCollector: null,
+        })
+        .signers([user.owner])
+        .rpc();
+
+      expect(await provider.connection.getBalance(user.userAccount)).to.equal(0);
+      // the owner account is closed with its last user account
+      expect(await provider.connection.getAccountInfo(user.ownerAccount)).to.be.null;
+    });
+
+    it("rejects a token account not owned by a token program", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+
+      await expectError(
+        program.methods
+          .exitUserAccount({ userAccountNonce: user.nonce })
+          .accountsPartial({
+            owner: user.owner.publicKey,
+            tradeFeeCollector: null,
+          })
+          .remainingAccounts(
+            [user.userAccount, Keypair.generate().publicKey, Keypair.generate().publicKey].map(
+              (pubkey) => ({ pubkey, isSigner: false, isWritable: true })
+            )
+          )
+          .signers([user.owner])
+          .rpc(),
+        "UserTokenAccountInvalid"
+      );
+    });
+
+    it("moves token balances to the owner and closes the token accounts", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL / 10);
+      await fundTokenAccount(user.tokenAccount, jupiterMarket.mint, tokens);
+
+      await exitUserAccount(user, null, [
+        [user.tokenAccount, jupiterMarket.mint],
+        [user.wsolTokenAccount, NATIVE_MINT],
+      ]);
+
+      expect(await tokenBalance(associatedTokenAddress(user.owner.publicKey, jupiterMarket.mint))).to.equal(tokens);
+      expect(await provider.connection.getAccountInfo(user.tokenAccount)).to.be.null;
+      expect(await provider.connection.getAccountInfo(user.wsolTokenAccount)).to.be.null;
+      // the rent of the token accounts is withdrawn with the balance
+      expect(await provider.connection.getBalance(user.userAccount)).to.equal(0);
+    });
+
+    it("settles the due fee to the fee collector", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens }),
+        jupiterRouteAccounts(user, true),
+        true
+      );
+      const fee = await dueFee(user);
+      expect(fee).to.equal(amountIn / 100);
+      const before = await provider.connection.getBalance(tradeFeeCollector);
+
+      await exitUserAccount(user, tradeFeeCollector, [
+        [user.tokenAccount, jupiterMarket.mint],
+        [user.wsolTokenAccount, NATIVE_MINT],
+      ]);
+
+      const after = await provider.connection.getBalance(tradeFeeCollector);
+      expect(after - before).to.equal(fee);
+      expect(await tokenBalance(associatedTokenAddress(user.owner.publicKey, jupiterMarket.mint))).to.equal(tokens);
+      expect(await provider.connection.getBalance(user.userAccount)).to.equal(0);
+    });
+
+    it("rejects an exit without a fee collector when fee is due", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens }),
+        jupiterRouteAccounts(user, true),
+        true
+      );
+
+      await expectError(exitUserAccount(user, null), "FeeCollectorMissing");
+    });
+
+    it("closes the owner account only with its last user account", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      await program.methods
+        .createUserAccount({ nonce: 1 })
+        .accounts({ owner: user.owner.publicKey })
+        .signers([user.owner])
+        .rpc();
+      const secondUser = { ...user, userAccount: userAccountPda(user.ownerAccount, 1), nonce: 1 };
+
+      await exitUserAccount(user, null);
+
+      const ownerAccount = await program.account.ownerAccount.fetch(user.ownerAccount);
+      expect(ownerAccount.createdUserAccounts.map((info) => info.nonce)).to.deep.equal([1]);
+
+      await exitUserAccount(secondUser, null);
+
+      expect(await provider.connection.getAccountInfo(user.ownerAccount)).to.be.null;
+    });
+  });
+
+  describe("set_owner_swap_limits", () => {
+    it("stores the owner caps in their own account and removes them with 0", async () => {
+      const user = await createUser(0);
+      const ownerSwapLimits = pda(Buffer.from("swap_limits"), user.ownerAccount.toBuffer());
+
+      await program.methods
+        .setOwnerSwapLimits(100, 50)
+        .accounts({ owner: user.owner.publicKey })
+        .signers([user.owner])
+        .rpc();
+      const limits = await program.account.ownerSwapLimits.fetch(ownerSwapLimits);
+      expect(limits.maxSlippageBps).to.equal(100);
+      expect(limits.maxPlatformFeeBps).to.equal(50);
+
+      await program.methods
+        .setOwnerSwapLimits(0, 0)
+        .accounts({ owner: user.owner.publicKey })
+        .signers([user.owner])
+        .rpc();
+      expect(await provider.connection.getAccountInfo(ownerSwapLimits)).to.be.null;
+    });
+
+    it("rejects a slippage cap above 100%", async () => {
+      const user = await createUser(0);
+
+      await expectError(
+        program.methods
+          .setOwnerSwapLimits(10_001, 0)
+          .accounts({ owner: user.owner.publicKey })
+          .signers([user.owner])
+          .rpc(),
+        "SlippageLimitInvalid"
+      );
+    });
+
+    it("rejects a platform fee cap above 100%", async () => {
+      const user = await createUser(0);
+
+      await expectError(
+        program.methods
+          .setOwnerSwapLimits(0, 10_001)
+          .accounts({ owner: user.owner.publicKey })
+          .signers([user.owner])
+          .rpc(),
+        "PlatformFeeLimitInvalid"
+      );
+    });
+  });
+
+  describe("timelock", () => {
+    it("executes a proposal only once its eta has passed", async () => {
+      await propose(manage({ setTimelockDelay: { delay: new anchor.BN(2) } }));
+
+      const id = await queueProposal(manage({ setTimelockDelay: { delay: new anchor.BN(0) } }));
+      await expectError(executeProposal(id), "ProposalNotReady");
+
+      await sleep(4);
+      await executeProposal(id);
+      const registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.timelockDelay.toNumber()).to.equal(0);
+    });
+
+    it("rejects a timelock delay above the limit", async () => {
+      const id = await queueProposal(
+        manage({ setTimelockDelay: { delay: new anchor.BN(365 * 24 * 60 * 60) } })
+      );
+      await expectError(executeProposal(id), "TimelockDelayInvalid");
+
+      await cancelProposal(id);
+    });
+  });
+
+  describe("authority signers", () => {
+    it("queues a proposal only with threshold approvals", async () => {
+      const [k1, k2] = [Keypair.generate(), Keypair.generate()];
+      await propose(
+        manage({ setAuthoritySigners: { signers: [authority, k1.publicKey, k2.publicKey], threshold: 2 } })
+      );
+
+      const reset = manage({ setAuthoritySigners: { signers: [], threshold: 0 } });
+      await expectError(queueProposal(reset), "OperatorRegistryAuthorityNotAllowed");
+
+      await propose(reset, [], [k1]);
+      const registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.authorityThreshold).to.equal(0);
+      expect(registry.authoritySigners).to.be.empty;
+    });
+
+    it("rejects a threshold above the signer count", async () => {
+      const id = await queueProposal(
+        manage({ setAuthoritySigners: { signers: [authority], threshold: 2 } })
+      );
+      await expectError(executeProposal(id), "AuthoritySignersInvalid");
+
+      await cancelProposal(id);
+    });
+  });
+
+  describe("authority transfer", () => {
+    it("transfers the authority once the pending authority accepts", async () => {
+      const newAuthority = Keypair.generate();
+      await fund(newAuthority.publicKey, LAMPORTS_PER_SOL);
+      await propose(manage({ proposeAuthority: { newAuthority: newAuthority.publicKey } }));
+
+      await program.methods
+        .acceptAuthority()
+        .accounts({ newAuthority: newAuthority.publicKey })
+        .signers([newAuthority])
+        .rpc();
+      let registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.operatorRegistryAuthority.toBase58()).to.equal(newAuthority.publicKey.toBase58());
+
+      // hand the authority back to the provider wallet for the other tests
+      const id = registry.proposalCount;
+      await program.methods
+        .queueProposal({ action: manage({ proposeAuthority: { newAuthority: authority } }) })
+        .accountsPartial({
+          proposal: proposalPda(id),
+          operatorRegistryAuthority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+      await program.methods
+        .executeProposal()
+        .accountsPartial({
+          proposal: proposalPda(id),
+          proposer: newAuthority.publicKey,
+        })
+        .rpc();
+      await program.methods.acceptAuthority().accounts({ newAuthority: authority }).rpc();
+
+      registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.operatorRegistryAuthority.toBase58()).to.equal(authority.toBase58());
+    });
+
+    it("clears the authority signers of the old authority on acceptance", async () => {
+      const k1 = Keypair.generate();
+      const newAuthority = Keypair.generate();
+      await fund(newAuthority.publicKey, LAMPORTS_PER_SOL);
+      await propose(manage({ setAuthoritySigners: { signers: [authority, k1.publicKey], threshold: 2 } }));
+      await propose(manage({ proposeAuthority: { newAuthority: newAuthority.publicKey } }), [], [k1]);
+
+      await program.methods
+        .acceptAuthority()
+        .accounts({ newAuthority: newAuthority.publicKey })
+        .signers([newAuthority])
+        .rpc();
+      const registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.authoritySigners).to.be.empty;
+      expect(registry.authorityThreshold).to.equal(0);
+
+      // the new authority approves alone, hand the authority back to the provider wallet
+      const id = registry.proposalCount;
+      await program.methods
+        .queueProposal({ action: manage({ proposeAuthority: { newAuthority: authority } }) })
+        .accountsPartial({
+          proposal: proposalPda(id),
+          operatorRegistryAuthority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc();
+      await program.methods
+        .executeProposal()
+        .accountsPartial({
+          proposal: proposalPda(id),
+          proposer: newAuthority.publicKey,
+        })
+        .rpc();
+      await program.methods.acceptAuthority().accounts({ newAuthority: authority }).rpc();
+    });
+
+    it("rejects acceptance by anyone but the pending authority", async () => {
+      const newAuthority = Keypair.generate();
+      await propose(manage({ proposeAuthority: { newAuthority: newAuthority.publicKey } }));
+
+      const other = Keypair.generate();
+      await expectError(
+        program.methods
+          .acceptAuthority()
+          .accounts({ newAuthority: other.publicKey })
+          .signers([other])
+          .rpc(),
+        "PendingAuthorityNotAllowed"
+      );
+
+      await propose(manage({ cancelAuthorityTransfer: {} }));
+      const registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.pendingAuthority.toBase58()).to.equal(PublicKey.default.toBase58());
+    });
+  });
+
+  describe("pausers", () => {
+    const pauseRegistry = pda(Buffer.from("pause"));
+
+    it("lets an accepted pauser pause and the authority unpause", async () => {
+      const pauser = Keypair.generate();
+      await propose({ addPauser: { pauser: pauser.publicKey } });
+      await program.methods
+        .acceptPauser()
+        .accounts({ newPauser: pauser.publicKey })
+        .signers([pauser])
+        .rpc();
+
+      await program.methods
+        .pause(new anchor.BN(60))
+        .accounts({ pauser: pauser.publicKey })
+        .signers([pauser])
+        .rpc();
+      let registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.programStatus).to.have.property("paused");
+      await expectError(createUser(0), "ProgramStatusError");
+
+      await program.methods.unpause().accountsPartial({ operatorRegistryAuthority: authority }).rpc();
+      registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.programStatus).to.have.property("initialized");
+
+      await propose({ removePauser: { pauser: pauser.publicKey } });
+      const pauses = await program.account.pauseRegistry.fetch(pauseRegistry);
+      expect(pauses.pausers.map((x) => x.toBase58())).to.deep.equal([authority.toBase58()]);
+    });
+
+    it("lets the authority pause only with the approval of the authority signers", async () => {
+      const k1 = Keypair.generate();
+      // the program pause lifted above must be out of its cooldown
+      await propose(manage({ setMaxPauseDuration: { duration: new anchor.BN(1) } }));
+      await sleep(2);
+      await propose({ removePauser: { pauser: authority } });
+      await propose(manage({ setAuthoritySigners: { signers: [authority, k1.publicKey], threshold: 2 } }));
+
+      await expectError(
+        program.methods.pause(new anchor.BN(1)).accounts({ pauser: authority }).rpc(),
+        "OperatorRegistryAuthorityNotAllowed"
+      );
+      await program.methods
+        .pause(new anchor.BN(1))
+        .accounts({ pauser: authority })
+        .remainingAccounts(asApprovals([k1]))
+        .signers([k1])
+        .rpc();
+      const registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.programStatus).to.have.property("paused");
+
+      await program.methods
+        .unpause()
+        .accountsPartial({ operatorRegistryAuthority: authority })
+        .remainingAccounts(asApprovals([k1]))
+        .signers([k1])
+        .rpc();
+      await propose(manage({ setAuthoritySigners: { signers: [], threshold: 0 } }), [], [k1]);
+      await propose({ addPauser: { pauser: authority } });
+      await program.methods.acceptPauser().accounts({ newPauser: authority }).rpc();
+      await propose(manage({ setMaxPauseDuration: { duration: new anchor.BN(7 * 24 * 60 * 60) } }));
+    });
+
+    it("rejects pausing by an account that is not a pauser", async () => {
+      const other = Keypair.generate();
+      await expectError(
+        program.methods
+          .pause(new anchor.BN(60))
+          .accounts({ pauser: other.publicKey })
+          .signers([other])
+          .rpc(),
+        "PauserNotAllowed"
+      );
+    });
+
+    it("rejects acceptance by anyone but the pending pauser", async () => {
+      await propose({ addPauser: { pauser: Keypair.generate().publicKey } });
+
+      const other = Keypair.generate();
+      await expectError(
+        program.methods
+          .acceptPauser()
+          .accounts({ newPauser: other.publicKey })
+          .signers([other])
+          .rpc(),
+        "PendingPauserNotAllowed"
+      );
+
+      await propose({ cancelPauserTransfer: {} });
+    });
+
+    it("rejects migrating a pause registry that is already in the current layout", async () => {
+      await expectError(
+        program.methods
+          .migratePauseRegistry()
+          .accountsPartial({ operatorRegistryAuthority: authority })
+          .rpc(),
+        "RegistryMigrationInvalid"
+      );
+    });
+  });
+
+  describe("feature pauses", () => {
+
+    it("pauses account creation alone until the authority lifts it", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+
+      await pauseFeatures(FEATURE_ACCOUNT_CREATION, 60);
+      await expectError(createUser(0), "FeaturePaused");
+
+      // other features keep running
+      await program.methods
+        .withdraw({
+          userAccountNonce: user.nonce,
+          withdrawOp: { withdraw: { amount: new anchor.BN(LAMPORTS_PER_SOL / 20) } },
+        })
+        .accountsPartial({
+          owner: user.owner.publicKey,
+          tradeFeeCollector: null,
+        })
+        .signers([user.owner])
+        .rpc();
+
+      await unpauseFeatures(FEATURE_ACCOUNT_CREATION);
+      const registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.pausedFeatures).to.equal(0);
+      await createUser(0);
+    });
+
+    it("rejects unknown feature bits", async () => {
+      await expectError(pauseFeatures(1 << 15, 60), "PauseFeaturesInvalid");
+    });
+  });
+
+  describe("pause expiry", () => {
+    const setMaxPauseDuration = (duration: number) =>
+      propose(manage({ setMaxPauseDuration: { duration: new anchor.BN(duration) } }));
+
+    it("lifts program and feature pauses once they expire", async () => {
+      await setMaxPauseDuration(2);
+      // the program pause lifted in the pausers tests must be out of its cooldown
+      await sleep(3);
+
+      await program.methods.pause(new anchor.BN(2)).accounts({ pauser: authority }).rpc();
+      await expectError(createUser(0), "ProgramStatusError");
+      await sleep(4);
+      await createUser(0);
+
+      await pauseFeatures(FEATURE_ACCOUNT_CREATION, 2);
+      await expectError(createUser(0), "FeaturePaused");
+      await sleep(4);
+      await createUser(0);
+    });
+
+    it("rejects pauses longer than the cap or inside the cooldown", async () => {
+      await setMaxPauseDuration(60);
+
+      await expectError(
+        program.methods.pause(new anchor.BN(61)).accounts({ pauser: authority }).rpc(),
+        "PauseDurationInvalid"
+      );
+      // the last program and feature pauses expired less than 60 seconds ago
+      await expectError(
+        program.methods.pause(new anchor.BN(1)).accounts({ pauser: authority }).rpc(),
+        "PauseCooldown"
+      );
+      await expectError(pauseFeatures(FEATURE_ACCOUNT_CREATION, 1), "PauseCooldown");
+
+      await setMaxPauseDuration(7 * 24 * 60 * 60);
+    });
+  });
+
+  describe("operator roles", () => {
+    it("lets each operator act within its roles", async () => {
+      const maintainer = await addOperator(ROLE_ACCOUNT_MAINTAINER);
+      const trader = await addOperator(ROLE_TRADER);
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+
+      const wsolTokenAccount = await createWsolAccount(user, maintainer);
+      await wrapSol(user, wsolTokenAccount, trader, LAMPORTS_PER_SOL / 20);
+
+      const balance = await provider.connection.getTokenAccountBalance(wsolTokenAccount);
+      expect(balance.value.amount).to.equal(String(LAMPORTS_PER_SOL / 20));
+    });
+
+    it("rejects an operator without the required role", async () => {
+      const trader = await addOperator(ROLE_TRADER);
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+
+      await expectError(createWsolAccount(user, trader), "OperatorNotAllowed");
+    });
+
+    it("rejects unknown role bits", async () => {
+      const id = await queueProposal(
+        manage({ addOperator: { operators: [Keypair.generate().publicKey], roles: 1 << 7 } })
+      );
+      await expectError(executeProposal(id), "OperatorRolesInvalid");
+      await cancelProposal(id);
+    });
+  });
+
+  describe("operator quotas", () => {
+    it("keeps an operator active until it expires", async () => {
+      const maintainer = await addOperator(ROLE_ACCOUNT_MAINTAINER);
+      const expiresAt = Math.floor(Date.now() / 1000) + 3600;
+      await setOperatorQuota(maintainer.publicKey, expiresAt, LAMPORTS_PER_SOL);
+
+      const account = await program.account.operatorAccount.fetch(operatorAccountPda(maintainer.publicKey));
+      expect(account.expiresAt.toNumber()).to.equal(expiresAt);
+      expect(account.maxVolumePerEpoch.toNumber()).to.equal(LAMPORTS_PER_SOL);
+
+      await createWsolAccount(await createUser(LAMPORTS_PER_SOL / 10), maintainer);
+    });
+
+    it("rejects an expired operator", async () => {
+      const maintainer = await addOperator(ROLE_ACCOUNT_MAINTAINER);
+      await setOperatorQuota(maintainer.publicKey, 1, 0);
+
+      await expectError(
+        createWsolAccount(await createUser(LAMPORTS_PER_SOL / 10), maintainer),
+        "OperatorNotAllowed"
+      );
+    });
+
+    it("limits the SOL volume an operator swaps per epoch", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      await setOperatorQuota(user.operator.publicKey, 0, LAMPORTS_PER_SOL / 10);
+
+      const buy = (amountIn: number) =>
+        swapOnJupiter(
+          user,
+          jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: 1_000 }),
+          jupiterRouteAccounts(user, true),
+          true
+        );
+
+      await buy(LAMPORTS_PER_SOL / 20);
+      // exceeds the quota on its own, so it fails even in a new epoch
+      await expectError(buy(LAMPORTS_PER_SOL / 5), "OperatorVolumeQuotaExceeded");
+    });
+  });
+
+  describe("operator accounts", () => {
+    it("registers more operators than the legacy 32-operator limit", async () => {
+      const operators: PublicKey[] = [];
+      for (let batch = 0; batch < 4; batch++) {
+        const keys = [...Array(10)].map(() => Keypair.generate().publicKey);
+        await propose(
+          manage({ addOperator: { operators: keys, roles: ROLE_TRADER } }),
+          keys.map(operatorAccountPda)
+        );
+        operators.push(...keys);
+      }
+
+      const accounts = await program.account.operatorAccount.fetchMultiple(operators.map(operatorAccountPda));
+      accounts.forEach((account, i) => {
+        expect(account.operator.toBase58()).to.equal(operators[i].toBase58());
+        expect(account.roles).to.equal(ROLE_TRADER);
+      });
+
+      await propose(manage({ removeOperator: { operators: [operators[0]] } }), [
+        operatorAccountPda(operators[0]),
+      ]);
+      expect(await provider.connection.getAccountInfo(operatorAccountPda(operators[0]))).to.be.null;
+    });
+
+    it("rejects an operator account that is not the operator's PDA", async () => {
+      const id = await queueProposal(
+        manage({ addOperator: { operators: [Keypair.generate().publicKey], roles: ROLE_TRADER } })
+      );
+      await expectError(
+        executeProposal(id, [operatorAccountPda(Keypair.generate().publicKey)]),
+        "OperatorAccountInvalid"
+      );
+      await cancelProposal(id);
+    });
+
+    it("rejects migrating a registry that is already in the current layout", async () => {
+      await expectError(
+        program.methods
+          .migrateOperatorRegistry()
+          .accountsPartial({ operatorRegistryAuthority: authority })
+          .rpc(),
+        "RegistryMigrationInvalid"
+      );
+    });
+  });
+
+  describe("owner_close_token_accounts", () => {
+    const closeTokenAccounts = (user, tokenAccounts: PublicKey[]) =>
+      program.methods
+        .ownerCloseTokenAccounts({ userAccountNonce: user.nonce })
+        .accountsPartial({
+          userAccount: user.userAccount,
+          owner: user.owner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(asRemaining(tokenAccounts))
+        .signers([user.owner])
+        .rpc();
+
+    it("closes a WSOL token account back into the user account", async () => {
+      const [maintainer, trader] = [
+        await addOperator(ROLE_ACCOUNT_MAINTAINER),
+        await addOperator(ROLE_TRADER),
+      ];
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      const wsolTokenAccount = await createWsolAccount(user, maintainer);
+      await wrapSol(user, wsolTokenAccount, trader, LAMPORTS_PER_SOL / 20);
+      const tokenAccountLamports = await provider.connection.getBalance(wsolTokenAccount);
+      const before = await provider.connection.getBalance(user.userAccount);
+
+      await closeTokenAccounts(user, [wsolTokenAccount]);
+
+      expect(await provider.connection.getAccountInfo(wsolTokenAccount)).to.be.null;
+      const after = await provider.connection.getBalance(user.userAccount);
+      expect(after - before).to.equal(tokenAccountLamports);
+    });
+
+    it("rejects a token account of another user account", async () => {
+      const maintainer = await addOperator(ROLE_ACCOUNT_MAINTAINER);
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      const other = await createUser(LAMPORTS_PER_SOL / 10);
+      const otherTokenAccount = await createWsolAccount(other, maintainer);
+
+      await expectError(closeTokenAccounts(user, [otherTokenAccount]), "UserTokenAccountInvalid");
+    });
+  });
+
+  describe("unwrap_wsol", () => {
+    const unwrapWsol = (user, wsolTokenAccount: PublicKey, trader: Keypair, amount: number) =>
+      program.methods
+        .unwrapWsol({ userAccountNonce: user.nonce, amount: new anchor.BN(amount) })
+        .accountsPartial({
+          wsolTokenAccount,
+          nativeMint: NATIVE_MINT,
+          userAccount: user.userAccount,
+          ownerAccount: user.ownerAccount,
+          operator: trader.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([trader])
+        .rpc();
+
+    let user;
+    let trader: Keypair;
+    let wsolTokenAccount: PublicKey;
+
+    before(async () => {
+      trader = await addOperator(ROLE_TRADER);
+      user = await createUser(LAMPORTS_PER_SOL / 10);
+      wsolTokenAccount = await createWsolAccount(user, await addOperator(ROLE_ACCOUNT_MAINTAINER));
+      await wrapSol(user, wsolTokenAccount, trader, LAMPORTS_PER_SOL / 20);
+    });
+
+    it("unwraps part of the WSOL balance into the user account", async () => {
+      const before = await provider.connection.getBalance(user.userAccount);
+
+      await unwrapWsol(user, wsolTokenAccount, trader, LAMPORTS_PER_SOL / 50);
+
+      const after = await provider.connection.getBalance(user.userAccount);
+      expect(after - before).to.equal(LAMPORTS_PER_SOL / 50);
+      const balance = await provider.connection.getTokenAccountBalance(wsolTokenAccount);
+      expect(balance.value.amount).to.equal(String(LAMPORTS_PER_SOL / 20 - LAMPORTS_PER_SOL / 50));
+    });
+
+    it("rejects unwrapping more than the WSOL balance", async () => {
+      await expectError(
+        unwrapWsol(user, wsolTokenAccount, trader, LAMPORTS_PER_SOL),
+        "UnwrapAmountInvalid"
+      );
+    });
+  });
+
+  describe("collect_fee_to_operator", () => {
+    const computeBudget = [
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 200_000 }),
+      ComputeBudgetProgram.setComputeUnitPrice({ microLamports: 1_000 }),
+    ];
+    // 5000 per signature plus 200_000 units at 1_000 micro-lamports
+    const txFee = 5_000 + 200;
+
+    let collector: Keypair;
+    let tradeFeeCollector: PublicKey;
+
+    before(async () => {
+      collector = await addOperator(ROLE_FEE_COLLECTOR);
+      await fund(collector.publicKey, LAMPORTS_PER_SOL);
+      tradeFeeCollector = Keypair.generate().publicKey;
+      await propose({ manageFee: { op: { addFeeCollector: { feeCollectors: [tradeFeeCollector] } } } });
+    });
+
+    const collectFeeToOperator = (user) =>
+      program.methods
+        .collectFeeToOperator({ userAccountNonce: user.nonce })
+        .accountsPartial({
+          userAccount: user.userAccount,
+          ownerAccount: user.ownerAccount,
+          operator: collector.publicKey,
+          tradeFeeCollector,
+        })
+        .signers([collector]);
+
+    it("reimburses the tx fee to the signing operator", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      const before = await provider.connection.getBalance(collector.publicKey);
+
+      await collectFeeToOperator(user).preInstructions(computeBudget).rpc();
+
+      const after = await provider.connection.getBalance(collector.publicKey);
+      expect(after - before).to.equal(txFee);
+      const account = await program.account.operatorAccount.fetch(operatorAccountPda(collector.publicKey));
+      expect(account.txFeePaid.toNumber()).to.equal(txFee);
+      expect(account.txFeeRecovered.toNumber()).to.equal(txFee);
+    });
+
+    it("rejects a second reimbursement in the same transaction", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      const first = await collectFeeToOperator(user).instruction();
+
+      await expectError(
+        collectFeeToOperator(user)
+          .preInstructions([...computeBudget, first])
+          .rpc(),
+        "TxFeeAlreadyInTransaction"
+      );
+    });
+  });
+
+  describe("tip_jito", () => {
+    // loaded from tests/fixtures/accounts
+    const jitoTipAccount = new PublicKey("96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5");
+    const jitoNonTipAccount = new PublicKey("AoSM83Ryi9W17UEZGQqqJz96wesbzrrwN9stpuput1EJ");
+    const tipAmount = LAMPORTS_PER_SOL / 1000;
+
+    const tipJito = (user, tipper: Keypair, account: PublicKey) =>
+      program.methods
+        .tipJito({ userAccountNonce: user.nonce, tipAmount: new anchor.BN(tipAmount) })
+        .accountsPartial({
+          userAccount: user.userAccount,
+          ownerAccount: user.ownerAccount,
+          operator: tipper.publicKey,
+          jitoTipAccount: account,
+        })
+        .signers([tipper])
+        .rpc();
+
+    it("tips a canonical tip account", async () => {
+      const tipper = await addOperator(ROLE_TIPPER);
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      const before = await provider.connection.getBalance(jitoTipAccount);
+
+      await tipJito(user, tipper, jitoTipAccount);
+
+      const after = await provider.connection.getBalance(jitoTipAccount);
+      expect(after - before).to.equal(tipAmount);
+    });
+
+    it("rejects a Jito account that is not a canonical tip account", async () => {
+      const tipper = await addOperator(ROLE_TIPPER);
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+
+      await expectError(tipJito(user, tipper, jitoNonTipAccount), "JitoTipAccountInvalid");
+    });
+  });
+
+  describe("events", () => {
+    it("emits an event for every state change of an instruction", async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+
+      const signature = await program.methods
+        .withdraw({ userAccountNonce: user.nonce, withdrawOp: { withdrawAll: {} } })
+        .accountsPartial({
+          owner: user.owner.publicKey,
+          tradeFeeCollector: null,
+        })
+        .signers([user.owner])
+        .rpc({ commitment: "confirmed" });
+
+      const events = await cpiEvents(signature);
+      expect(events.map((event) => event.name)).to.deep.equal(["withdrawEvent", "userAccountClosedEvent"]);
+      expect(events[0].data.userAccount.toBase58()).to.equal(user.userAccount.toBase58());
+    });
+
+    it("rejects an event instruction not signed by the event authority", async () => {
+      const eventAuthority = PublicKey.findProgramAddressSync(
+        [Buffer.from("__event_authority")],
+        program.programId
+      )[0];
+      const eventIxTag = Buffer.from("e4a545ea51cb9a1d", "hex");
+      const forged = new anchor.web3.TransactionInstruction({
+        programId: program.programId,
+        keys: [{ pubkey: eventAuthority, isSigner: false, isWritable: false }],
+        data: Buffer.concat([eventIxTag, Buffer.alloc(8)]),
+      });
+
+      await expectError(
+        provider.sendAndConfirm(new anchor.web3.Transaction().add(forged)),
+        // ConstraintSigner
+        "custom program error: 0x7d2"
+      );
+    });
+  });
+
+  describe("swap_on_jupiter auto wrap", () => {
+    const amountIn = LAMPORTS_PER_SOL / 10;
+    const tokens = 1_000_000;
+
+    it("wraps SOL for a buy and unwraps the SOL proceeds of a sell", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+
+      let before = await provider.connection.getBalance(user.userAccount);
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens }),
+        jupiterRouteAccounts(user, true),
+        true
+      );
+      let after = await provider.connection.getBalance(user.userAccount);
+      expect(before - after).to.equal(amountIn);
+      expect(await tokenBalance(user.tokenAccount)).to.equal(tokens);
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(0);
+      // 1% swap fee
+      expect(await dueFee(user)).to.equal(amountIn / 100);
+
+      before = after;
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: tokens, quotedAmount: amountIn }),
+        jupiterRouteAccounts(user, false),
+        true
+      );
+      after = await provider.connection.getBalance(user.userAccount);
+      expect(after - before).to.equal(amountIn);
+      expect(await tokenBalance(user.tokenAccount)).to.equal(0);
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(0);
+    });
+
+    it("rejects auto wrap without the temporary WSOL token account", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+
+      await expectError(
+        swapOnJupiter(
+          user,
+          jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens }),
+          jupiterRouteAccounts(user, true),
+          true,
+          { tempWsolTokenAccount: null }
+        ),
+        "AutoWrapAccountsMissing"
+      );
+    });
+  });
+
+  describe("swap_on_jupiter shared accounts route", () => {
+    const amountIn = LAMPORTS_PER_SOL / 10;
+    const tokens = 1_000_000;
+
+    it("swaps through a shared accounts route", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      await wrapSol(user, user.wsolTokenAccount, user.operator, amountIn);
+
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_SHARED_ACCOUNTS_ROUTE, { amount: amountIn, quotedAmount: tokens, id: 0 }),
+        jupiterRouteAccounts(user, true, { route: JUPITER_SHARED_ACCOUNTS_ROUTE, id: 0 })
+      );
+
+      expect(await tokenBalance(user.tokenAccount)).to.equal(tokens);
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(0);
+    });
+
+    it("rejects a program authority of another id", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      await wrapSol(user, user.wsolTokenAccount, user.operator, amountIn);
+
+      await expectError(
+        swapOnJupiter(
+          user,
+          jupiterData(JUPITER_SHARED_ACCOUNTS_ROUTE, { amount: amountIn, quotedAmount: tokens, id: 1 }),
+          jupiterRouteAccounts(user, true, { route: JUPITER_SHARED_ACCOUNTS_ROUTE, id: 0 })
+        ),
+        "JupiterRouteSourceInvalid"
+      );
+    });
+  });
+
+  describe("swap_on_jupiter exact out route", () => {
+    const quotedIn = LAMPORTS_PER_SOL / 20;
+    const tokensOut = 500_000;
+
+    it("buys an exact amount and unwraps the unused input", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      const before = await provider.connection.getBalance(user.userAccount);
+
+      // at most quotedIn plus 1% slippage is wrapped
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_EXACT_OUT_ROUTE, { amount: tokensOut, quotedAmount: quotedIn, slippageBps: 100 }),
+        jupiterRouteAccounts(user, true, { route: JUPITER_EXACT_OUT_ROUTE }),
+        true
+      );
+
+      const after = await provider.connection.getBalance(user.userAccount);
+      expect(before - after).to.equal(quotedIn);
+      expect(await tokenBalance(user.tokenAccount)).to.equal(tokensOut);
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(0);
+    });
+
+    it("rejects a route paying out to another token account", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+
+      await expectError(
+        swapOnJupiter(
+          user,
+          jupiterData(JUPITER_EXACT_OUT_ROUTE, { amount: tokensOut, quotedAmount: quotedIn }),
+          jupiterRouteAccounts(user, true, {
+            route: JUPITER_EXACT_OUT_ROUTE,
+            destinationTokenAccount: jupiterMarket.tokenVault,
+          }),
+          true
+        ),
+        "JupiterRouteDestinationInvalid"
+      );
+    });
+  });
+
+  describe("swap_on_jupiter route limits", () => {
+    const amountIn = LAMPORTS_PER_SOL / 10;
+    const tokens = 1_000_000;
+
+    const setOwnerSwapLimits = (user, maxSlippageBps: number) =>
+      program.methods
+        .setOwnerSwapLimits(maxSlippageBps, 0)
+        .accounts({ owner: user.owner.publicKey })
+        .signers([user.owner])
+        .rpc();
+
+    it("swaps within the protocol and owner slippage caps", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      await setOwnerSwapLimits(user, 300);
+
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens, slippageBps: 300 }),
+        jupiterRouteAccounts(user, true),
+        true
+      );
+      expect(await tokenBalance(user.tokenAccount)).to.equal(tokens);
+    });
+
+    it("rejects slippage above the protocol cap", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+
+      // initialized with a 500 bps cap
+      await expectError(
+        swapOnJupiter(
+          user,
+          jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens, slippageBps: 501 }),
+          jupiterRouteAccounts(user, true),
+          true
+        ),
+        "JupiterSlippageExceeded"
+      );
+    });
+
+    it("rejects slippage above the owner cap", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      await setOwnerSwapLimits(user, 100);
+
+      await expectError(
+        swapOnJupiter(
+          user,
+          jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens, slippageBps: 200 }),
+          jupiterRouteAccounts(user, true),
+          true
+        ),
+        "JupiterSlippageExceeded"
+      );
+    });
+
+    it("rejects route data too short for its trailing fields", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+
+      await expectError(
+        swapOnJupiter(user, Buffer.from([...JUPITER_ROUTE, 0, 0]), jupiterRouteAccounts(user, true), true),
+        "JupiterRouteDecodeFailed"
+      );
+    });
+
+    it("decodes the trailing fields of a route with a swap variant unknown to the IDL", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens, swapVariant: 200 }),
+        jupiterRouteAccounts(user, true),
+        true
+      );
+      expect(await tokenBalance(user.tokenAccount)).to.equal(tokens);
+    });
+
+    it("rejects a shared route too short for its program authority id", async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL);
+      const data = jupiterData(JUPITER_SHARED_ACCOUNTS_ROUTE, { amount: amountIn, quotedAmount: tokens });
+
+      // discriminator and trailing fields only, without the id and the route plan
+      await expectError(
+        swapOnJupiter(
+          user,
+          Buffer.concat([data.subarray(0, 8), data.subarray(12)]),
+          jupiterRouteAccounts(user, true, { route: JUPITER_SHARED_ACCOUNTS_ROUTE }),
+          true
+        ),
+        "JupiterRouteDecodeFailed"
+      );
+    });
+  });
+
+  describe("swap_on_jupiter platform fee", () => {
+    const tokens = 1_000_000;
+    const quotedOut = LAMPORTS_PER_SOL / 10;
+    let feeRegistryWsolAccount: PublicKey;
+
+    before(async () => {
+      await propose({
+        manageFee: {
+          op: { setFee: { feesToSet: [{ feeIndex: { maxPlatformFeeBps: {} }, value: new anchor.BN(100) }] } },
+        },
+      });
+      feeRegistryWsolAccount = await createTokenAccount(pda(Buffer.from("fee")), NATIVE_MINT);
+    });
+
+    // a seller of `tokens`
+    const createSeller = async () => {
+      const user = await createSwapUser(LAMPORTS_PER_SOL / 10);
+      await fundTokenAccount(user.tokenAccount, jupiterMarket.mint, tokens);
+      return user;
+    };
+
+    const sell = (user, platformFeeBps: number, platformFeeAccount: PublicKey, chargedPlatformFeeBps?: number) =>
+      swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: tokens, quotedAmount: quotedOut, platformFeeBps, chargedPlatformFeeBps }),
+        jupiterRouteAccounts(user, false, { platformFeeAccount })
+      );
+
+    it("takes the trade fee as platform fee instead of a due fee", async () => {
+      const user = await createSeller();
+      const before = await tokenBalance(feeRegistryWsolAccount);
+
+      await sell(user, 100, feeRegistryWsolAccount);
+
+      expect((await tokenBalance(feeRegistryWsolAccount)) - before).to.equal(quotedOut / 100);
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(quotedOut - quotedOut / 100);
+      expect(await dueFee(user)).to.equal(0);
+    });
+
+    it("charges a platform fee shortfall as due fee", async () => {
+      const user = await createSeller();
+
+      // half of the declared platform fee is charged
+      await sell(user, 100, feeRegistryWsolAccount, 50);
+
+      // swap fee 1% of 99_500_000, half of it is not covered by the platform fee
+      expect(await dueFee(user)).to.equal(497_500);
+    });
+
+    it("rejects a platform fee account not owned by the fee registry", async () => {
+      const user = await createSeller();
+
+      await expectError(sell(user, 100, user.wsolTokenAccount), "PlatformFeeAccountInvalid");
+    });
+
+    it("rejects a platform fee below the swap fee", async () => {
+      const user = await createSeller();
+
+      await expectError(sell(user, 50, feeRegistryWsolAccount), "PlatformFeeTooLow");
+    });
+
+    it("rejects a platform fee above the owner cap", async () => {
+      const user = await createSeller();
+      await program.methods
+        .setOwnerSwapLimits(0, 50)
+        .accounts({ owner: user.owner.publicKey })
+        .signers([user.owner])
+        .rpc();
+
+      await expectError(sell(user, 100, feeRegistryWsolAccount), "JupiterPlatformFeeExceeded");
+    });
+  });
+
+  // runs against the mainnet SOL-USDC pool, dumped with `yarn dump:raydium-amm` and `yarn dump:raydium-pool`
+  describe("swap_on_raydium_amm", () => {
+    const RAYDIUM_AMM_PROGRAM_ID = new PublicKey("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8");
+    const RAYDIUM_AMM_AUTHORITY = new PublicKey("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1");
+    const RAYDIUM_SOL_USDC_POOL = new PublicKey("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2");
+    const amountIn = LAMPORTS_PER_SOL / 10;
+
+    let poolAccounts;
+    let usdcMint: PublicKey;
+
+    // the Raydium binaries and the pool are only loaded by `yarn test:raydium`, see README
+    before(async function () {
+      if (!(await provider.connection.getAccountInfo(RAYDIUM_AMM_PROGRAM_ID))) {
+        this.skip();
+      }
+      const amm = (await provider.connection.getAccountInfo(RAYDIUM_SOL_USDC_POOL)).data;
+      // AmmInfo pubkeys from offset 336, see swap_on_raydium_amm
+      const ammPubkeyAt = (index: number) => new PublicKey(amm.subarray(336 + 32 * index, 368 + 32 * index));
+      const market = ammPubkeyAt(6);
+      const marketProgram = ammPubkeyAt(7);
+      const marketData = (await provider.connection.getAccountInfo(market)).data;
+      const marketPubkeyAt = (offset: number) => new PublicKey(marketData.subarray(offset, offset + 32));
+
+      usdcMint = ammPubkeyAt(3);
+      poolAccounts = {
+        raydiumAmmProgram: RAYDIUM_AMM_PROGRAM_ID,
+        amm: RAYDIUM_SOL_USDC_POOL,
+        ammAuthority: RAYDIUM_AMM_AUTHORITY,
+        ammOpenOrders: ammPubkeyAt(5),
+        ammTargetOrders: ammPubkeyAt(8),
+        poolCoinTokenAccount: ammPubkeyAt(0),
+        poolPcTokenAccount: ammPubkeyAt(1),
+        marketProgram,
+        market,
+        marketBids: marketPubkeyAt(285),
+        marketAsks: marketPubkeyAt(317),
+        marketEventQueue: marketPubkeyAt(253),
+        marketCoinVault: marketPubkeyAt(117),
+        marketPcVault: marketPubkeyAt(165),
+        marketVaultSigner: PublicKey.createProgramAddressSync(
+          [market.toBuffer(), marketData.subarray(45, 53)],
+          marketProgram
+        ),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+    });
+
+    // a user account holding amountIn WSOL, and a USDC token account
+    const createRaydiumUser = async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      const operator = await addOperator(ROLE_TRADER | ROLE_ACCOUNT_MAINTAINER);
+      const wsolTokenAccount = await createWsolAccount(user, operator);
+      const usdcTokenAccount = await createUserTokenAccount(user, operator, usdcMint);
+      await fundTokenAccount(wsolTokenAccount, NATIVE_MINT, amountIn);
+      return { ...user, operator, wsolTokenAccount, usdcTokenAccount };
+    };
+
+    const buyUsdc = (user, minAmountOut: number, accounts = {}) =>
+      program.methods
+        .swapOnRaydiumAmm({
+          userAccountNonce: user.nonce,
+          amountIn: new anchor.BN(amountIn),
+          minAmountOut: new anchor.BN(minAmountOut),
+        })
+        .accountsPartial({
+          userAccount: user.userAccount,
+          ownerAccount: user.ownerAccount,
+          operator: user.operator.publicKey,
+          userSourceTokenAccount: user.wsolTokenAccount,
+          userDestinationTokenAccount: user.usdcTokenAccount,
+          ...poolAccounts,
+          ...accounts,
+        })
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })])
+        .signers([user.operator])
+        .rpc();
+
+    it("swaps WSOL for USDC in the pool", async () => {
+      const user = await createRaydiumUser();
+
+      await buyUsdc(user, 1);
+
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(0);
+      expect(await tokenBalance(user.usdcTokenAccount)).to.be.greaterThan(0);
+      // 1% swap fee
+      expect(await dueFee(user)).to.equal(amountIn / 100);
+    });
+
+    it("rejects pool accounts that are not the pool's", async () => {
+      const user = await createRaydiumUser();
+
+      await expectError(
+        buyUsdc(user, 1, {
+          poolCoinTokenAccount: poolAccounts.poolPcTokenAccount,
+          poolPcTokenAccount: poolAccounts.poolCoinTokenAccount,
+        }),
+        "RaydiumPoolInvalid"
+      );
+    });
+
+    it("rejects a swap without slippage protection", async () => {
+      const user = await createRaydiumUser();
+
+      await expectError(buyUsdc(user, 0), "RaydiumAmountInvalid");
+    });
+  });
+
+  // PumpSwap runs against the mock PumpSwap program in tests/programs/mock_pumpswap,
+  // it fills trades at their limit from the token accounts of its pools
+  describe("swap_on_pumpswap", () => {
+    const PUMPSWAP_PROGRAM_ID = new PublicKey("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+    const PUMPFUN_PROGRAM_ID = new PublicKey("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+    // tests/fixtures/accounts/pumpfun_complete_bonding_curve.json
+    const COMPLETE_BONDING_CURVE = new PublicKey("Hhdy5DMb6StFvsUR4M2oFU2tYHraFp2PnZTZx2DAt5fs");
+    const amountIn = LAMPORTS_PER_SOL / 10;
+    const tokens = 1_000_000;
+
+    let mint: PublicKey;
+    let pool: PublicKey;
+    let poolBaseTokenAccount: PublicKey;
+    let poolQuoteTokenAccount: PublicKey;
+
+    before(async () => {
+      mint = await createMint();
+      pool = PublicKey.findProgramAddressSync([Buffer.from("pool"), mint.toBuffer()], PUMPSWAP_PROGRAM_ID)[0];
+      poolBaseTokenAccount = await createTokenAccount(pool, mint);
+      await fundTokenAccount(poolBaseTokenAccount, mint, 1_000_000_000_000);
+      poolQuoteTokenAccount = await createTokenAccount(pool, NATIVE_MINT);
+      await fundTokenAccount(poolQuoteTokenAccount, NATIVE_MINT, 10 * LAMPORTS_PER_SOL);
+
+      const tx = new anchor.web3.Transaction().add(
+        new anchor.web3.TransactionInstruction({
+          programId: PUMPSWAP_PROGRAM_ID,
+          keys: [
+            { pubkey: authority, isSigner: true, isWritable: true },
+            writable(pool),
+            readonly(mint),
+            readonly(NATIVE_MINT),
+            readonly(poolBaseTokenAccount),
+            readonly(poolQuoteTokenAccount),
+            readonly(SystemProgram.programId),
+          ],
+          // create_pool of the mock
+          data: Buffer.from([233, 146, 209, 142, 207, 104, 64, 188]),
+        })
+      );
+      await provider.sendAndConfirm(tx);
+    });
+
+    // a user account holding amountIn WSOL, and a token account of the pool token
+    const createPumpswapUser = async () => {
+      const user = await createUser(LAMPORTS_PER_SOL / 10);
+      const operator = await addOperator(ROLE_TRADER | ROLE_ACCOUNT_MAINTAINER);
+      const wsolTokenAccount = await createWsolAccount(user, operator);
+      const tokenAccount = await createUserTokenAccount(user, operator, mint);
+      await fundTokenAccount(wsolTokenAccount, NATIVE_MINT, amountIn);
+      return { ...user, operator, wsolTokenAccount, tokenAccount };
+    };
+
+    // accounts of a PumpSwap buy or sell, accounts the mock does not read are filled with its id
+    const pumpswapAccounts = (user, { poolBase = poolBaseTokenAccount, poolQuote = poolQuoteTokenAccount } = {}) => [
+      writable(pool),
+      readonly(user.userAccount),
+      readonly(PUMPSWAP_PROGRAM_ID), // global config
+      readonly(mint),
+      readonly(NATIVE_MINT),
+      writable(user.tokenAccount),
+      writable(user.wsolTokenAccount),
+      writable(poolBase),
+      writable(poolQuote),
+      readonly(PUMPSWAP_PROGRAM_ID), // protocol fee recipient
+      readonly(PUMPSWAP_PROGRAM_ID), // protocol fee recipient token account
+      readonly(TOKEN_PROGRAM_ID),
+      readonly(TOKEN_PROGRAM_ID),
+      readonly(SystemProgram.programId),
+      readonly(ASSOCIATED_TOKEN_PROGRAM_ID),
+      readonly(PUMPSWAP_PROGRAM_ID), // event authority
+      readonly(PUMPSWAP_PROGRAM_ID),
+    ];
+
+    const swapOnPumpswap = (user, op, remainingAccounts = pumpswapAccounts(user)) =>
+      program.methods
+        .swapOnPumpswap({ userAccountNonce: user.nonce, op })
+        .accountsPartial({
+          userAccount: user.userAccount,
+          ownerAccount: user.ownerAccount,
+          operator: user.operator.publicKey,
+          pumpswapProgram: PUMPSWAP_PROGRAM_ID,
+        })
+        .remainingAccounts(remainingAccounts)
+        .signers([user.operator])
+        .rpc();
+
+    const buy = { buy: { baseAmountOut: new anchor.BN(tokens), maxQuoteAmountIn: new anchor.BN(amountIn) } };
+    const sell = { sell: { baseAmountIn: new anchor.BN(tokens), minQuoteAmountOut: new anchor.BN(amountIn) } };
+
+    it("buys and sells a graduated token in its pool", async () => {
+      const user = await createPumpswapUser();
+
+      await swapOnPumpswap(user, buy);
+      expect(await tokenBalance(user.tokenAccount)).to.equal(tokens);
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(0);
+      // 1% swap fee
+      expect(await dueFee(user)).to.equal(amountIn / 100);
+
+      await swapOnPumpswap(user, sell);
+      expect(await tokenBalance(user.tokenAccount)).to.equal(0);
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(amountIn);
+      expect(await dueFee(user)).to.equal((2 * amountIn) / 100);
+    });
+
+    it("rejects pool token accounts that are not the pool's", async () => {
+      const user = await createPumpswapUser();
+
+      await expectError(
+        swapOnPumpswap(
+          user,
+          buy,
+          pumpswapAccounts(user, { poolBase: poolQuoteTokenAccount, poolQuote: poolBaseTokenAccount })
+        ),
+        "PumpswapPoolInvalid"
+      );
+    });
+
+    it("rejects a swap without slippage protection", async () => {
+      const user = await createPumpswapUser();
+
+      await expectError(
+        swapOnPumpswap(user, { buy: { baseAmountOut: new anchor.BN(tokens), maxQuoteAmountIn: new anchor.BN(0) } }),
+        "PumpswapAmountInvalid"
+      );
+    });
+
+    it("rejects a pump.fun trade once the bonding curve is complete", async () => {
+      const user = await createPumpswapUser();
+
+      await expectError(
+        program.methods
+          .swapOnPumpfunV2({
+            userAccountNonce: user.nonce,
+            op: { buy: { solAmount: new anchor.BN(amountIn), minTokenOutput: new anchor.BN(tokens) } },
+          })
+          .accountsPartial({
+            userAccount: user.userAccount,
+            ownerAccount: user.ownerAccount,
+            operator: user.operator.publicKey,
+            pumpfunProgram: PUMPFUN_PROGRAM_ID,
+          })
+          .remainingAccounts([
+            readonly(PUMPFUN_PROGRAM_ID), // global
+            readonly(PUMPFUN_PROGRAM_ID), // fee recipient
+            readonly(mint),
+            writable(COMPLETE_BONDING_CURVE),
+            readonly(PUMPFUN_PROGRAM_ID), // associated bonding curve
+            writable(user.tokenAccount),
+            readonly(user.userAccount),
+          ])
+          .signers([user.operator])
+          .rpc(),
+        "PumpfunBondingCurveComplete"
+      );
+    });
+  });
+
+  // wind down is irreversible, it must stay the last test of the suite
+  describe("wind down", () => {
+    const amountIn = LAMPORTS_PER_SOL / 10;
+    const tokens = 1_000_000;
+    let user;
+
+    before(async () => {
+      // a user holding tokens and WSOL when the program winds down
+      user = await createSwapUser(LAMPORTS_PER_SOL / 10);
+      await fundTokenAccount(user.tokenAccount, jupiterMarket.mint, tokens);
+      await fundTokenAccount(user.wsolTokenAccount, NATIVE_MINT, amountIn);
+
+      await propose(manage({ windDown: {} }));
+      const registry = await program.account.operatorRegistry.fetch(operatorRegistry);
+      expect(registry.programStatus).to.have.property("windDown");
+    });
+
+    it("keeps token sells and withdrawals open", async () => {
+      await swapOnJupiter(
+        user,
+        jupiterData(JUPITER_ROUTE, { amount: tokens, quotedAmount: amountIn }),
+        jupiterRouteAccounts(user, false)
+      );
+      expect(await tokenBalance(user.tokenAccount)).to.equal(0);
+      expect(await tokenBalance(user.wsolTokenAccount)).to.equal(2 * amountIn);
+
+      const before = await provider.connection.getBalance(user.userAccount);
+      await program.methods
+        .withdraw({
+          userAccountNonce: user.nonce,
+          withdrawOp: { withdraw: { amount: new anchor.BN(LAMPORTS_PER_SOL / 50) } },
+        })
+        .accountsPartial({
+          owner: user.owner.publicKey,
+          tradeFeeCollector: null,
+        })
+        .signers([user.owner])
+        .rpc();
+      const after = await provider.connection.getBalance(user.userAccount);
+      expect(before - after).to.equal(LAMPORTS_PER_SOL / 50);
+    });
+
+    it("rejects new accounts, WSOL wraps and buys", async () => {
+      await expectError(createUser(0), "ProgramStatusError");
+      await expectError(wrapSol(user, user.wsolTokenAccount, user.operator, amountIn), "ProgramStatusError");
+      await expectError(
+        swapOnJupiter(
+          user,
+          jupiterData(JUPITER_ROUTE, { amount: amountIn, quotedAmount: tokens }),
+          jupiterRouteAccounts(user, true)
+        ),
+        "WindDownSellOnly"
+      );
+    });
+  });
 });
@@ -0,0 +1,183 @@
+// stand-in for the Jupiter aggregator program in local tests, deployed at the Jupiter program id,
+// it reads the trailing fields of the route instruction data like swap_on_jupiter does,
+// and fills the route from vault token accounts appended after the route accounts:
+// ..., vault_authority, source_vault, destination_vault
+// vault_authority is the PDA of this program seeded by VAULT_AUTHORITY_SEED
+//
+// exact in routes take in_amount and pay out quoted_out_amount,
+// exact out routes take quoted_in_amount and pay out out_amount,
+// the route plan is not decoded, if it holds a step the percent of the first step
+// is the platform fee bps actually charged, otherwise the declared platform_fee_bps is charged
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+};
+
+declare_id!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+#[program]
+pub mod mock_jupiter {
+    use super::*;
+
+    // every instruction falls through to the fallback, the mock matches the discriminators itself
+    pub fn fallback<'info>(program_id: &Pubkey, accounts: &'info [AccountInfo<'info>], data: &[u8]) -> Result<()> {
+        process_instruction(program_id, accounts, data).map_err(Into::into)
+    }
+}
+
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault_authority";
+
+const ROUTE_DISCRIMINATOR: &[u8] = &[229, 23, 203, 151, 122, 227, 173, 42];
+const SHARED_ACCOUNT_ROUTE_DISCRIMINATOR: &[u8] = &[193, 32, 155, 51, 65, 214, 156, 129];
+const EXACT_OUT_ROUTE_DISCRIMINATOR: &[u8] = &[208, 51, 239, 151, 123, 43, 237, 92];
+const SHARED_ACCOUNT_EXACT_OUT_ROUTE_DISCRIMINATOR: &[u8] = &[176, 209, 105, 168, 154, 125, 69, 62];
+
+// amount (u64), quoted amount (u64), slippage_bps (u16), platform_fee_bps (u8)
+const TAIL_LEN: usize = 8 + 8 + 2 + 1;
+const MAX_BPS: u128 = 10_000;
+
+// account indexes of a route type, same as the real Jupiter instructions
+struct RouteLayout {
+    exact_out: bool,
+    shared: bool,
+    user_transfer_authority: usize,
+    source: usize,
+    destination: usize,
+    platform_fee_account: usize,
+}
+
+impl RouteLayout {
+    fn of(discriminator: &[u8]) -> std::result::Result<Self, ProgramError> {
+        let (exact_out, shared) = match discriminator {
+            ROUTE_DISCRIMINATOR => (false, false),
+            SHARED_ACCOUNT_ROUTE_DISCRIMINATOR => (false, true),
+            EXACT_OUT_ROUTE_DISCRIMINATOR => (true, false),
+            SHARED_ACCOUNT_EXACT_OUT_ROUTE_DISCRIMINATOR => (true, true),
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        Ok(match (exact_out, shared) {
+            (false, false) => Self { exact_out, shared, user_transfer_authority: 1, source: 2, destination: 3, platform_fee_account: 6 },
+            (true, false) => Self { exact_out, shared, user_transfer_authority: 1, source: 2, destination: 3, platform_fee_account: 7 },
+            (_, true) => Self { exact_out, shared, user_transfer_authority: 2, source: 3, destination: 6, platform_fee_account: 9 },
+        })
+    }
+}
+
+fn account<'a, 'info>(accounts: &'a [AccountInfo<'info>], index: usize) -> std::result::Result<&'a AccountInfo<'info>, ProgramError> {
+    accounts.get(index).ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+fn token_mint(token_account: &AccountInfo) -> std::result::Result<Pubkey, ProgramError> {
+    let data = token_account.try_borrow_data()?;
+    let mint = data.get(..32).ok_or(ProgramError::InvalidAccountData)?;
+    Ok(Pubkey::new_from_array(mint.try_into().unwrap()))
+}
+
+fn bps_of(amount: u64, bps: u64) -> u64 {
+    (u128::from(amount) * u128::from(bps) / MAX_BPS) as u64
+}
+
+// SPL Token transfer, the same instruction is accepted by Token-2022 for tokens without extensions
+fn transfer<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let mut data = vec![3];
+    data.extend_from_slice(&amount.to_le_bytes());
+    invoke_signed(
+        &Instruction {
+            program_id: *token_program.key,
+            accounts: vec![
+                AccountMeta::new(*from.key, false),
+                AccountMeta::new(*to.key, false),
+                AccountMeta::new_readonly(*authority.key, true),
+            ],
+            data,
+        },
+        &[from.clone(), to.clone(), authority.clone(), token_program.clone()],
+        signer_seeds,
+    )
+}
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let layout = RouteLayout::of(data.get(..8).ok_or(ProgramError::InvalidInstructionData)?)?;
+    let tail_start = data
+        .len()
+        .checked_sub(TAIL_LEN)
+        .filter(|tail_start| *tail_start >= 8)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let tail = &data[tail_start..];
+    let amount = u64::from_le_bytes(tail[0..8].try_into().unwrap());
+    let quoted_amount = u64::from_le_bytes(tail[8..16].try_into().unwrap());
+    let declared_platform_fee_bps = u64::from(tail[18]);
+
+    // shared routes start with the program authority id
+    let route_plan_start = if layout.shared { 9 } else { 8 };
+    // route plan length (u32), then swap (one byte for variants without fields), percent, input_index, output_index
+    let charged_platform_fee_bps = match data.get(route_plan_start..tail_start) {
+        Some(route_plan) if route_plan.len() >= 6 && route_plan[..4] != [0; 4] => u64::from(route_plan[5]),
+        _ => declared_platform_fee_bps,
+    };
+
+    let vaults_start = accounts
+        .len()
+        .checked_sub(3)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let [vault_authority, source_vault, destination_vault] = &accounts[vaults_start..] else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let (vault_authority_key, bump) = Pubkey::find_program_address(&[VAULT_AUTHORITY_SEED], program_id);
+    if vault_authority.key != &vault_authority_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let vault_signer_seeds: &[&[&[u8]]] = &[&[VAULT_AUTHORITY_SEED, &[bump]]];
+
+    let token_program = account(accounts, 0)?;
+    let user_transfer_authority = account(accounts, layout.user_transfer_authority)?;
+    let source = account(accounts, layout.source)?;
+    let destination = account(accounts, layout.destination)?;
+    // Jupiter passes its own program id for a missing optional account
+    let platform_fee_account = accounts
+        .get(layout.platform_fee_account)
+        .filter(|platform_fee_account| charged_platform_fee_bps > 0 && platform_fee_account.key != program_id);
+
+    let (mut in_amount, mut out_amount) = if layout.exact_out {
+        (quoted_amount, amount)
+    } else {
+        (amount, quoted_amount)
+    };
+
+    // exact in routes take the platform fee out of the traded amount, exact out routes on top of it
+    if let Some(platform_fee_account) = platform_fee_account {
+        if token_mint(platform_fee_account)? == token_mint(destination_vault)? {
+            let platform_fee = bps_of(out_amount, charged_platform_fee_bps);
+            transfer(token_program, destination_vault, platform_fee_account, vault_authority, platform_fee, vault_signer_seeds)?;
+            if !layout.exact_out {
+                out_amount -= platform_fee;
+            }
+        } else {
+            let platform_fee = bps_of(in_amount, charged_platform_fee_bps);
+            transfer(token_program, source, platform_fee_account, user_transfer_authority, platform_fee, &[])?;
+            if !layout.exact_out {
+                in_amount -= platform_fee;
+            }
+        }
+    }
+
+    transfer(token_program, source, source_vault, user_transfer_authority, in_amount, &[])?;
+    transfer(token_program, destination_vault, destination, vault_authority, out_amount, vault_signer_seeds)?;
+
+    Ok(())
+}
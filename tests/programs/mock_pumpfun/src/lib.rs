@@ -0,0 +1,22 @@
+// stand-in for the pump.fun program in local tests, deployed at the pump.fun program id,
+// the tests only swap against bonding curves that swap_on_pumpfun_v2 rejects before the cpi,
+// so the program just has to exist, every instruction fails
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{entrypoint::ProgramResult, program_error::ProgramError};
+
+declare_id!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
+
+#[program]
+pub mod mock_pumpfun {
+    use super::*;
+
+    // every instruction falls through to the fallback
+    pub fn fallback<'info>(program_id: &Pubkey, accounts: &'info [AccountInfo<'info>], data: &[u8]) -> Result<()> {
+        process_instruction(program_id, accounts, data).map_err(Into::into)
+    }
+}
+
+pub fn process_instruction(_program_id: &Pubkey, _accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    msg!("mock pump.fun program, swaps are not supported");
+    Err(ProgramError::InvalidInstructionData)
+}
@@ -0,0 +1,173 @@
+// stand-in for the PumpSwap AMM program in local tests, deployed at the PumpSwap program id,
+// buy and sell take the accounts of the real instructions and fill at the limit of the trade:
+// buy takes max_quote_amount_in and pays out base_amount_out,
+// sell takes base_amount_in and pays out min_quote_amount_out
+//
+// pools are created by create_pool, which only exists in this mock:
+// payer, pool, base_mint, quote_mint, pool_base_token_account, pool_quote_token_account, system_program
+// the pool is the PDA of this program seeded by POOL_SEED and base_mint, and the authority of its token accounts
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    system_instruction,
+};
+
+declare_id!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+
+#[program]
+pub mod mock_pumpswap {
+    use super::*;
+
+    // every instruction falls through to the fallback, the mock matches the discriminators itself
+    pub fn fallback<'info>(program_id: &Pubkey, accounts: &'info [AccountInfo<'info>], data: &[u8]) -> Result<()> {
+        process_instruction(program_id, accounts, data).map_err(Into::into)
+    }
+}
+
+pub const POOL_SEED: &[u8] = b"pool";
+
+const CREATE_POOL_DISCRIMINATOR: &[u8] = &[233, 146, 209, 142, 207, 104, 64, 188];
+const BUY_DISCRIMINATOR: &[u8] = &[102, 6, 61, 18, 1, 218, 235, 234];
+const SELL_DISCRIMINATOR: &[u8] = &[51, 230, 133, 164, 1, 127, 131, 173];
+const POOL_DISCRIMINATOR: &[u8] = &[241, 154, 109, 4, 17, 177, 109, 188];
+
+// leading fields of the real pool account, as read by swap_on_pumpswap:
+// discriminator, pool_bump (u8), index (u16), creator, base_mint, quote_mint, lp_mint,
+// pool_base_token_account, pool_quote_token_account, lp_supply (u64)
+const POOL_LEN: usize = 8 + 1 + 2 + 32 * 6 + 8;
+const BASE_MINT_OFFSET: usize = 8 + 1 + 2 + 32;
+
+// account indexes of buy and sell, same as the real PumpSwap instructions
+const POOL: usize = 0;
+const USER: usize = 1;
+const USER_BASE_TOKEN_ACCOUNT: usize = 5;
+const USER_QUOTE_TOKEN_ACCOUNT: usize = 6;
+const POOL_BASE_TOKEN_ACCOUNT: usize = 7;
+const POOL_QUOTE_TOKEN_ACCOUNT: usize = 8;
+const BASE_TOKEN_PROGRAM: usize = 11;
+
+fn account<'a, 'info>(accounts: &'a [AccountInfo<'info>], index: usize) -> std::result::Result<&'a AccountInfo<'info>, ProgramError> {
+    accounts.get(index).ok_or(ProgramError::NotEnoughAccountKeys)
+}
+
+fn pubkey_at(data: &[u8], offset: usize) -> std::result::Result<Pubkey, ProgramError> {
+    let pubkey = data.get(offset..offset + 32).ok_or(ProgramError::InvalidAccountData)?;
+    Ok(Pubkey::new_from_array(pubkey.try_into().unwrap()))
+}
+
+// SPL Token transfer, pump.fun tokens and WSOL are both SPL Token mints
+fn transfer<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut data = vec![3];
+    data.extend_from_slice(&amount.to_le_bytes());
+    invoke_signed(
+        &Instruction {
+            program_id: *token_program.key,
+            accounts: vec![
+                AccountMeta::new(*from.key, false),
+                AccountMeta::new(*to.key, false),
+                AccountMeta::new_readonly(*authority.key, true),
+            ],
+            data,
+        },
+        &[from.clone(), to.clone(), authority.clone(), token_program.clone()],
+        signer_seeds,
+    )
+}
+
+fn create_pool(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let [payer, pool, base_mint, quote_mint, pool_base_token_account, pool_quote_token_account, system_program] = accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let (pool_key, bump) = Pubkey::find_program_address(&[POOL_SEED, base_mint.key.as_ref()], program_id);
+    if pool.key != &pool_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            pool.key,
+            Rent::get()?.minimum_balance(POOL_LEN),
+            POOL_LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), pool.clone(), system_program.clone()],
+        &[&[POOL_SEED, base_mint.key.as_ref(), &[bump]]],
+    )?;
+
+    let mut data = Vec::with_capacity(POOL_LEN);
+    data.extend_from_slice(POOL_DISCRIMINATOR);
+    data.push(bump);
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(payer.key.as_ref());
+    data.extend_from_slice(base_mint.key.as_ref());
+    data.extend_from_slice(quote_mint.key.as_ref());
+    data.extend_from_slice(Pubkey::default().as_ref()); // no lp mint
+    data.extend_from_slice(pool_base_token_account.key.as_ref());
+    data.extend_from_slice(pool_quote_token_account.key.as_ref());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    pool.try_borrow_mut_data()?.copy_from_slice(&data);
+
+    Ok(())
+}
+
+fn swap(program_id: &Pubkey, accounts: &[AccountInfo], buy: bool, amount: u64, limit: u64) -> ProgramResult {
+    let pool = account(accounts, POOL)?;
+    if pool.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let (base_mint, bump) = {
+        let data = pool.try_borrow_data()?;
+        if !data.starts_with(POOL_DISCRIMINATOR) || data.len() < POOL_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        (pubkey_at(&data, BASE_MINT_OFFSET)?, data[8])
+    };
+    let pool_signer_seeds: &[&[&[u8]]] = &[&[POOL_SEED, base_mint.as_ref(), &[bump]]];
+
+    let user = account(accounts, USER)?;
+    let user_base_token_account = account(accounts, USER_BASE_TOKEN_ACCOUNT)?;
+    let user_quote_token_account = account(accounts, USER_QUOTE_TOKEN_ACCOUNT)?;
+    let pool_base_token_account = account(accounts, POOL_BASE_TOKEN_ACCOUNT)?;
+    let pool_quote_token_account = account(accounts, POOL_QUOTE_TOKEN_ACCOUNT)?;
+    let token_program = account(accounts, BASE_TOKEN_PROGRAM)?;
+
+    if buy {
+        // base_amount_out, max_quote_amount_in
+        transfer(token_program, user_quote_token_account, pool_quote_token_account, user, limit, &[])?;
+        transfer(token_program, pool_base_token_account, user_base_token_account, pool, amount, pool_signer_seeds)
+    } else {
+        // base_amount_in, min_quote_amount_out
+        transfer(token_program, user_base_token_account, pool_base_token_account, user, amount, &[])?;
+        transfer(token_program, pool_quote_token_account, user_quote_token_account, pool, limit, pool_signer_seeds)
+    }
+}
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let discriminator = data.get(..8).ok_or(ProgramError::InvalidInstructionData)?;
+    if discriminator == CREATE_POOL_DISCRIMINATOR {
+        return create_pool(program_id, accounts);
+    }
+
+    let buy = match discriminator {
+        BUY_DISCRIMINATOR => true,
+        SELL_DISCRIMINATOR => false,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    let args = data.get(8..24).ok_or(ProgramError::InvalidInstructionData)?;
+    let amount = u64::from_le_bytes(args[0..8].try_into().unwrap());
+    let limit = u64::from_le_bytes(args[8..16].try_into().unwrap());
+
+    swap(program_id, accounts, buy, amount, limit)
+}